@@ -0,0 +1,315 @@
+use crate::hosting::ProviderKind;
+use crate::repository::{Repository, Tracking};
+use anyhow::{anyhow, Context, Result};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+lazy_static! {
+    static ref CONFIG: std::result::Result<Config, String> = load().map_err(|e| format!("{:?}", e));
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    pub clone_depth: Option<u32>,
+    pub timeout: Option<u64>,
+    pub retries: Option<u32>,
+    pub stale_after_days: Option<i64>,
+    pub hooks: HooksConfig,
+    pub github: GitHubConfig,
+    pub http: HttpConfig,
+    pub hosting: HostingsConfig,
+    pub plugins: HashMap<String, PluginConfig>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct HooksConfig {
+    pub pre_update: Option<String>,
+    pub post_update: Option<String>,
+    pub timeout: Option<u64>,
+    pub managers: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct GitHubConfig {
+    pub token: Option<String>,
+    pub cache_ttl: Option<i64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct HttpConfig {
+    pub proxy: Option<String>,
+    pub no_proxy: Vec<String>,
+    pub ca_certs: Vec<PathBuf>,
+    pub connect_timeout: Option<u64>,
+    pub timeout: Option<u64>,
+}
+
+impl HttpConfig {
+    pub fn connect_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.connect_timeout.unwrap_or(10))
+    }
+
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.timeout.unwrap_or(60))
+    }
+}
+
+// [hosting] holds settings shared by all hosts next to the per-host tables
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct HostingsConfig {
+    pub cache_ttl: Option<i64>,
+    #[serde(flatten)]
+    pub hosts: HashMap<String, HostingConfig>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct HostingConfig {
+    pub kind: Option<ProviderKind>,
+    pub base_url: Option<String>,
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PluginConfig {
+    pub track: Option<Tracking>,
+    pub clone_depth: Option<u32>,
+    pub timeout: Option<u64>,
+    pub post_update: Option<String>,
+}
+
+impl Config {
+    pub fn plugin(&self, repo: &Repository) -> Option<&PluginConfig> {
+        if let Ok(remote) = repo.remote_url() {
+            if let Some(p) = self.plugins.get(&remote.name_with_owner()) {
+                return Some(p);
+            }
+        }
+        let dir_name = std::path::Path::new(&repo.dir).file_name()?.to_str()?;
+        self.plugins.get(dir_name)
+    }
+
+    pub fn clone_depth(&self, repo: &Repository) -> Option<u32> {
+        self.plugin(repo)
+            .and_then(|p| p.clone_depth)
+            .or(self.clone_depth)
+    }
+
+    pub fn timeout(&self, repo: &Repository) -> std::time::Duration {
+        let secs = self
+            .plugin(repo)
+            .and_then(|p| p.timeout)
+            .or(self.timeout)
+            .unwrap_or(120);
+        std::time::Duration::from_secs(secs)
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries.unwrap_or(2)
+    }
+
+    pub fn stale_after(&self) -> chrono::Duration {
+        chrono::Duration::days(self.stale_after_days.unwrap_or(730))
+    }
+
+    // github.cache-ttl predates the other hosts and still works as a fallback
+    pub fn cache_ttl(&self) -> chrono::Duration {
+        let secs = self.hosting.cache_ttl.or(self.github.cache_ttl);
+        chrono::Duration::seconds(secs.unwrap_or(24 * 60 * 60))
+    }
+
+    pub fn post_update_hook(&self, repo: &Repository) -> Option<&str> {
+        self.plugin(repo)
+            .and_then(|p| p.post_update.as_deref())
+            .or_else(|| {
+                repo.manager
+                    .and_then(|m| self.hooks.managers.get(m.name()))
+                    .map(|s| s.as_str())
+            })
+    }
+
+    pub fn hook_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.hooks.timeout.unwrap_or(300))
+    }
+}
+
+pub fn get() -> Result<&'static Config> {
+    CONFIG.as_ref().map_err(|e| anyhow!("{}", e))
+}
+
+pub fn config_dir() -> Result<PathBuf> {
+    if let Ok(dir) = std::env::var("DOTPLUGS_CONFIG_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    let mut dir = dirs::config_dir().ok_or(anyhow!("Config dir not found"))?;
+    dir.push("dotplugs");
+    Ok(dir)
+}
+
+pub fn cache_dir() -> Result<PathBuf> {
+    if let Ok(dir) = std::env::var("DOTPLUGS_CACHE_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    let mut dir = dirs::cache_dir().ok_or(anyhow!("Cache dir not found"))?;
+    dir.push("dotplugs");
+    Ok(dir)
+}
+
+pub fn state_dir() -> Result<PathBuf> {
+    if let Ok(dir) = std::env::var("DOTPLUGS_STATE_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    let mut dir = match std::env::var("XDG_STATE_HOME") {
+        Ok(d) if !d.is_empty() => PathBuf::from(d),
+        _ => {
+            let mut d = dirs::home_dir().ok_or(anyhow!("Home env not found"))?;
+            d.push(".local/state");
+            d
+        }
+    };
+    dir.push("dotplugs");
+    Ok(dir)
+}
+
+fn load() -> Result<Config> {
+    let mut path = config_dir()?;
+    path.push("config.toml");
+    log::debug!("config file: {:?}", &path);
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let s = std::fs::read_to_string(&path)?;
+    let config = toml::from_str(&s).with_context(|| format!("while reading {:?}", &path))?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::Manager;
+
+    #[test]
+    fn parse_tracking_ok() -> Result<()> {
+        let data = r#"
+            [plugins."neoclide/coc.nvim"]
+            track = "latest-tag"
+
+            [plugins."vim-go"]
+            track = { tag-pattern = "v1.*" }
+
+            [plugins."tpope/vim-fugitive"]
+            track = "branch"
+        "#;
+        let config: Config = toml::from_str(data)?;
+        assert_eq!(
+            config.plugins["neoclide/coc.nvim"].track,
+            Some(Tracking::LatestTag)
+        );
+        assert_eq!(
+            config.plugins["vim-go"].track,
+            Some(Tracking::TagPattern("v1.*".to_string()))
+        );
+        assert_eq!(
+            config.plugins["tpope/vim-fugitive"].track,
+            Some(Tracking::Branch)
+        );
+
+        let repo = Repository {
+            uri: "https://github.com/fatih/vim-go.git".to_string(),
+            dir: "/home/test/.vim/plugged/vim-go".to_string(),
+            ..Default::default()
+        };
+        assert!(config.plugin(&repo).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_hooks_ok() -> Result<()> {
+        let data = r#"
+            [hooks]
+            post-update = "echo done"
+            timeout = 60
+
+            [hooks.managers]
+            vim-plug = "vim -es -c 'helptags ALL' -c q"
+
+            [plugins."nvim-telescope/telescope-fzf-native.nvim"]
+            post-update = "make"
+        "#;
+        let config: Config = toml::from_str(data)?;
+        assert_eq!(config.hooks.post_update, Some("echo done".to_string()));
+        assert_eq!(config.hook_timeout().as_secs(), 60);
+
+        let mut repo = Repository {
+            uri: "https://github.com/nvim-telescope/telescope-fzf-native.nvim".to_string(),
+            dir: "/home/test/.vim/plugged/telescope-fzf-native.nvim".to_string(),
+            manager: Some(Manager::VimPlug),
+            ..Default::default()
+        };
+        assert_eq!(config.post_update_hook(&repo), Some("make"));
+        repo.uri = "https://github.com/tpope/vim-fugitive".to_string();
+        repo.dir = "/home/test/.vim/plugged/vim-fugitive".to_string();
+        assert_eq!(
+            config.post_update_hook(&repo),
+            Some("vim -es -c 'helptags ALL' -c q")
+        );
+        repo.manager = Some(Manager::Tpm);
+        assert_eq!(config.post_update_hook(&repo), None);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_hosting_ok() -> Result<()> {
+        let data = r#"
+            [hosting."git.example.com"]
+            kind = "forgejo"
+            base-url = "https://git.example.com/api/v1"
+
+            [hosting."github.example.com"]
+            kind = "github"
+            base-url = "https://github.example.com/api/graphql"
+            token = "ghe_xxxxxxxx"
+        "#;
+        let config: Config = toml::from_str(data)?;
+        let hosting = &config.hosting.hosts["git.example.com"];
+        assert_eq!(hosting.kind, Some(ProviderKind::Gitea));
+        assert_eq!(
+            hosting.base_url.as_deref(),
+            Some("https://git.example.com/api/v1")
+        );
+        let enterprise = &config.hosting.hosts["github.example.com"];
+        assert_eq!(enterprise.kind, Some(ProviderKind::GitHub));
+        assert_eq!(enterprise.token.as_deref(), Some("ghe_xxxxxxxx"));
+        assert_eq!(config.cache_ttl().num_hours(), 24);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_cache_ttl_ok() -> Result<()> {
+        let config: Config = toml::from_str("[github]\ncache-ttl = 600\n")?;
+        assert_eq!(config.cache_ttl().num_seconds(), 600);
+
+        let data = r#"
+            [github]
+            cache-ttl = 600
+
+            [hosting]
+            cache-ttl = 3600
+
+            [hosting."gitlab.example.com"]
+            kind = "gitlab"
+        "#;
+        let config: Config = toml::from_str(data)?;
+        assert_eq!(config.cache_ttl().num_seconds(), 3600);
+        assert_eq!(config.hosting.hosts.len(), 1);
+        Ok(())
+    }
+}
@@ -1,10 +1,103 @@
-use crate::git::{GitStatus, UpdateStatus};
+use crate::git::{GitStatus, SubmoduleState, UpdateStatus};
+use crate::hosting::attention::Attention;
+use crate::repository::RemoteUrl;
 use colored::Colorize;
 
 pub fn display(statuses: &Vec<GitStatus>) {
     for s in statuses {
-        if s.status == UpdateStatus::Required {
-            println!("{} {}", s.uri, format!("{:?}", s.status).red());
+        if s.status == UpdateStatus::Required && s.latest_tag.is_some() {
+            println!(
+                "{} {} → {}",
+                name(&s.uri),
+                s.current_tag.as_deref().unwrap_or("untagged").red(),
+                s.latest_tag.as_deref().unwrap_or_default().green()
+            );
+        } else if s.status.needs_update() {
+            println!("{} {}", name(&s.uri), format!("{:?}", s.status).red());
+        } else if s.status.needs_repair() {
+            println!(
+                "{} {} {} → {} (use --follow-default-branch)",
+                name(&s.uri),
+                format!("{:?}", s.status).yellow(),
+                s.branch,
+                s.default_branch.as_deref().unwrap_or("?")
+            );
+        } else if s.status == UpdateStatus::NotInstalled {
+            println!(
+                "{} {} (use dotplugs install)",
+                name(&s.uri),
+                format!("{:?}", s.status).yellow()
+            );
+        } else if s.status == UpdateStatus::ForcePushed {
+            // Resetting could drop local commits, so update never does it by itself
+            println!(
+                "{} {} (upstream history was rewritten; to take it, run git -C {} reset --hard origin/{})",
+                name(&s.uri),
+                format!("{:?}", s.status).yellow(),
+                s.dir,
+                s.branch
+            );
+        } else if s.status == UpdateStatus::TimedOut {
+            println!(
+                "{} {} (raise timeout in config.toml)",
+                name(&s.uri),
+                format!("{:?}", s.status).yellow()
+            );
+        }
+        for sm in &s.submodules {
+            if s.status == UpdateStatus::SubmoduleRequired {
+                println!("    {} {}", sm.path, format!("{:?}", sm.state).yellow());
+            } else if sm.state == SubmoduleState::UpstreamMoved {
+                println!(
+                    "{} submodule {} {} (kept at the commit the plugin records)",
+                    name(&s.uri),
+                    sm.path,
+                    format!("{:?}", sm.state).yellow()
+                );
+            }
         }
     }
 }
+
+pub fn display_attention(statuses: &Vec<GitStatus>) {
+    if statuses.iter().all(|s| s.attention.is_empty()) {
+        return;
+    }
+    println!("{}", "Attention".bold());
+    for s in statuses {
+        for a in &s.attention {
+            match a {
+                Attention::NotFound => println!(
+                    "    {} {} (deleted, private or inaccessible)",
+                    name(&s.uri),
+                    "NotFound".red()
+                ),
+                Attention::Archived => {
+                    println!("    {} {}", name(&s.uri), "Archived".yellow());
+                }
+                Attention::Stale { pushed_at } => println!(
+                    "    {} {} (no push since {})",
+                    name(&s.uri),
+                    "Stale".yellow(),
+                    pushed_at.format("%Y-%m-%d")
+                ),
+                Attention::Moved {
+                    name_with_owner,
+                    suggested_uri,
+                } => println!(
+                    "    {} {} to {} (use {})",
+                    name(&s.uri),
+                    "Moved".yellow(),
+                    name_with_owner,
+                    suggested_uri
+                ),
+            }
+        }
+    }
+}
+
+pub fn name(uri: &str) -> String {
+    RemoteUrl::parse(uri)
+        .map(|r| r.to_string())
+        .unwrap_or_else(|_| uri.to_string())
+}
@@ -1,15 +1,62 @@
 mod branch;
+mod clone;
 mod fetch;
+mod housekeeping;
+mod info;
+mod network;
+mod plan;
+mod pool;
+mod remote;
 mod status;
+mod submodule;
+mod tag;
 mod update;
 
+use crate::hosting::attention::Attention;
+use crate::repository::{Manager, Repository, Tracking};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub enum UpdateStatus {
     Required,
+    SubmoduleRequired,
     Already,
+    DefaultBranchChanged,
+    UpstreamGone,
+    ForcePushed,
+    NotInstalled,
     NotGitRepository,
+    TimedOut,
+}
+
+impl UpdateStatus {
+    pub fn needs_update(&self) -> bool {
+        matches!(
+            self,
+            UpdateStatus::Required | UpdateStatus::SubmoduleRequired
+        )
+    }
+
+    pub fn needs_repair(&self) -> bool {
+        matches!(
+            self,
+            UpdateStatus::DefaultBranchChanged | UpdateStatus::UpstreamGone
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub enum SubmoduleState {
+    Uninitialized,
+    OutOfSync,
+    UpstreamMoved,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubmoduleStatus {
+    pub name: String,
+    pub path: String,
+    pub state: SubmoduleState,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -18,7 +65,43 @@ pub struct GitStatus {
     pub dir: String,
     pub branch: String,
     pub status: UpdateStatus,
+    #[serde(default)]
+    pub submodules: Vec<SubmoduleStatus>,
+    #[serde(default)]
+    pub track: Tracking,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manager: Option<Manager>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attention: Vec<Attention>,
+}
+
+impl GitStatus {
+    pub fn repository(&self) -> Repository {
+        Repository {
+            uri: self.uri.to_string(),
+            dir: self.dir.to_string(),
+            track: self.track.clone(),
+            manager: self.manager,
+            ..Default::default()
+        }
+    }
 }
 
+pub use clone::clone_repositories as install;
+pub use housekeeping::{collect_garbage, get_disk_usages, GcMode};
+pub use info::{
+    get_commit_subjects, get_commit_time, get_last_commit_time, has_uncommitted_changes, show_log,
+};
+pub use plan::{plan_updates, UpdatePlan};
+pub use pool::map_in_pool;
+pub use remote::set_remote_name_with_owner;
 pub use status::get_status_async as get_status;
+pub use tag::{get_head_tag, get_tags_between};
 pub use update::update_repositories as update;
+pub use update::UpdateResult;
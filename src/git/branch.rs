@@ -9,6 +9,16 @@ pub fn get_current_branch(repo: &git2::Repository) -> Result<String> {
     Ok(branch.name()?.unwrap_or("master").to_string())
 }
 
+pub fn get_local_default_branch(repo: &git2::Repository) -> Option<String> {
+    let head = repo.find_reference("refs/remotes/origin/HEAD").ok()?;
+    let target = head.symbolic_target()?;
+    Some(
+        target
+            .trim_start_matches("refs/remotes/origin/")
+            .to_string(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,6 +60,7 @@ mod tests {
         let repo = Repository {
             uri: repo_url.to_string(),
             dir: target_git_dir,
+            ..Default::default()
         };
         let git_repo = git2::Repository::open(&repo.dir)?;
         let branch = get_current_branch(&git_repo)?;
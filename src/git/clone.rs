@@ -0,0 +1,97 @@
+use crate::git::network;
+use crate::git::pool;
+use crate::repository::Repositories;
+use crate::repository::Repository;
+use anyhow::{anyhow, Result};
+use log::warn;
+
+pub fn clone_repositories(repos: &Repositories) -> Result<Vec<Repository>> {
+    let config = crate::config::get()?;
+    let jobs: Vec<_> = repos
+        .iter()
+        .map(|r| (r.clone(), config.clone_depth(r)))
+        .collect();
+    let failures = pool::map_in_pool(jobs.clone(), |(repo, depth)| {
+        eprintln!("Install: {}", repo.uri);
+        match clone_repository_by_command(&repo, depth) {
+            Ok(_) => None,
+            Err(e) => {
+                warn!("{:?}: {:?}", &repo, e);
+                Some(repo)
+            }
+        }
+    })?;
+    Ok(failures
+        .into_iter()
+        .zip(jobs)
+        .filter_map(|(f, (repo, _))| match f {
+            Some(f) => f,
+            None => Some(repo),
+        })
+        .collect())
+}
+
+fn clone_repository_by_command(repo: &Repository, depth: Option<u32>) -> Result<()> {
+    let config = crate::config::get()?;
+    let mut cmd = network::command("git");
+    cmd.args(["clone", "--quiet", "--recurse-submodules"]);
+    if let Some(depth) = depth {
+        cmd.arg(format!("--depth={}", depth));
+        cmd.arg("--shallow-submodules");
+    }
+    if let Some(branch) = &repo.branch {
+        cmd.args(["--branch", branch]);
+    }
+    let output = network::run(
+        cmd.arg("--")
+            .arg(&repo.uri)
+            .arg(repo.dir.trim_end_matches('/')),
+        config.timeout(repo),
+        config.retries(),
+    )?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git clone is failure [Exit code: {}] {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{git, temp_dir};
+
+    #[test]
+    fn clone_repositories_ok() -> Result<()> {
+        let root = temp_dir("dotplugs_clone");
+        let upstream = format!("{}/upstream", root);
+        std::fs::create_dir_all(&upstream)?;
+        git(&["init"], &upstream)?;
+        git(&["commit", "--allow-empty", "-m", "first"], &upstream)?;
+        git(&["checkout", "-b", "stable"], &upstream)?;
+        git(&["commit", "--allow-empty", "-m", "second"], &upstream)?;
+
+        let repos = vec![
+            Repository {
+                uri: format!("file://{}", upstream),
+                dir: format!("{}/plugins/plugin/", root),
+                branch: Some("stable".to_string()),
+                ..Default::default()
+            },
+            Repository {
+                uri: format!("{}/missing", root),
+                dir: format!("{}/plugins/missing", root),
+                ..Default::default()
+            },
+        ];
+        let failures = clone_repositories(&repos)?;
+        assert_eq!(failures.len(), 1);
+        let git_repo = git2::Repository::open(format!("{}/plugins/plugin", root))?;
+        assert_eq!(crate::git::branch::get_current_branch(&git_repo)?, "stable");
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
+}
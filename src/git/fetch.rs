@@ -1,5 +1,7 @@
 use crate::git::branch;
+use crate::git::network;
 use crate::repository::Repository;
+use crate::repository::Tracking;
 use anyhow::Result;
 use log::warn;
 
@@ -23,16 +25,48 @@ fn fetch_repository_by_git2rs(repo: &Repository) -> Result<()> {
 }
 
 fn fetch_repository_by_command(repo: &Repository) -> Result<()> {
-    let output = std::process::Command::new("git")
-        .args(&["fetch", "-a"])
-        .current_dir(&repo.dir)
-        .output()?;
+    let config = crate::config::get()?;
+    let mut cmd = network::command("git");
+    cmd.args(["fetch", "-a", "-p", "--recurse-submodules=yes"]);
+    if repo.track != Tracking::Branch {
+        cmd.arg("--tags");
+    }
+    let output = network::run(
+        cmd.current_dir(&repo.dir),
+        config.timeout(repo),
+        config.retries(),
+    )?;
     if !output.status.success() {
         warn!("git fetch is failure [Exit code: {}]", output.status);
     }
     Ok(())
 }
 
+pub fn get_remote_default_branch(repo: &Repository) -> Result<Option<String>> {
+    let config = crate::config::get()?;
+    let output = network::run(
+        network::command("git")
+            .args(["ls-remote", "--symref", "origin", "HEAD"])
+            .current_dir(&repo.dir),
+        config.timeout(repo),
+        config.retries(),
+    )?;
+    if !output.status.success() {
+        warn!("git ls-remote is failure [Exit code: {}]", output.status);
+        return Ok(None);
+    }
+    Ok(parse_symref(&String::from_utf8(output.stdout)?))
+}
+
+fn parse_symref(ls_remote: &str) -> Option<String> {
+    ls_remote
+        .lines()
+        .filter_map(|l| l.strip_prefix("ref: refs/heads/"))
+        .filter_map(|l| l.strip_suffix("\tHEAD"))
+        .next()
+        .map(|b| b.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,6 +80,14 @@ mod tests {
             .try_init();
     }
 
+    #[test]
+    fn parse_symref_ok() {
+        let data = "ref: refs/heads/main\tHEAD\n\
+                    0123456789abcdef0123456789abcdef01234567\tHEAD\n";
+        assert_eq!(parse_symref(data), Some("main".to_string()));
+        assert_eq!(parse_symref(""), None);
+    }
+
     #[test]
     fn fetch_repository_status_for_shallow_ok() -> Result<()> {
         use boolinator::Boolinator;
@@ -100,6 +142,7 @@ mod tests {
         let repo = Repository {
             uri: "https://github.com/octocat/Spoon-Knife".to_string(),
             dir: target_git_dir.to_string(),
+            ..Default::default()
         };
         fetch_repository(&repo)?;
         let sha1_after = String::from_utf8(
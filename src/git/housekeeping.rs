@@ -0,0 +1,216 @@
+use crate::git::network;
+use crate::git::pool;
+use crate::repository::Repositories;
+use crate::repository::Repository;
+use anyhow::{anyhow, Result};
+use log::warn;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct DiskUsage {
+    pub uri: String,
+    pub worktree_size: u64,
+    pub git_size: u64,
+    pub loose_objects: u64,
+    pub packed_objects: u64,
+    pub shallow: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GcMode {
+    Gc,
+    Repack,
+    Shallow(u32),
+}
+
+#[derive(Debug)]
+pub struct GcResult {
+    pub uri: String,
+    pub before: u64,
+    pub after: u64,
+    pub error: Option<String>,
+}
+
+pub fn get_disk_usages(repos: &Repositories) -> Result<Vec<DiskUsage>> {
+    let usages = pool::map_in_pool(repos.clone(), |repo| match get_disk_usage(&repo) {
+        Ok(u) => Some(u),
+        Err(e) => {
+            warn!("{:?}: {:?}", &repo, e);
+            None
+        }
+    })?;
+    Ok(usages.into_iter().flatten().flatten().collect())
+}
+
+pub fn collect_garbage(repos: &Repositories, mode: GcMode) -> Result<Vec<GcResult>> {
+    let results = pool::map_in_pool(repos.clone(), move |repo| {
+        eprintln!("GC: {}", &repo.uri);
+        let before = get_git_dir_size(&repo).unwrap_or(0);
+        let error = collect_garbage_by_command(&repo, mode)
+            .err()
+            .map(|e| e.to_string());
+        GcResult {
+            after: get_git_dir_size(&repo).unwrap_or(0),
+            uri: repo.uri,
+            before,
+            error,
+        }
+    })?;
+    Ok(results
+        .into_iter()
+        .zip(repos)
+        .map(|(r, repo)| {
+            r.unwrap_or_else(|| GcResult {
+                uri: repo.uri.to_string(),
+                before: 0,
+                after: 0,
+                error: Some("interrupted".to_string()),
+            })
+        })
+        .collect())
+}
+
+fn get_disk_usage(repo: &Repository) -> Result<DiskUsage> {
+    let git_repo = git2::Repository::open(&repo.dir)?;
+    let total_size = crate::disk::get_dir_size(&repo.dir);
+    let git_size = crate::disk::get_dir_size(git_repo.path());
+    let worktree_size = if git_repo.path().starts_with(&repo.dir) {
+        total_size.saturating_sub(git_size)
+    } else {
+        total_size
+    };
+    let (loose_objects, packed_objects) = count_objects_by_command(&repo.dir)?;
+    Ok(DiskUsage {
+        uri: repo.uri.to_string(),
+        worktree_size,
+        git_size,
+        loose_objects,
+        packed_objects,
+        shallow: git_repo.is_shallow(),
+    })
+}
+
+fn get_git_dir_size(repo: &Repository) -> Result<u64> {
+    let git_repo = git2::Repository::open(&repo.dir)?;
+    Ok(crate::disk::get_dir_size(git_repo.path()))
+}
+
+fn count_objects_by_command<P: AsRef<Path>>(dir: P) -> Result<(u64, u64)> {
+    let output = std::process::Command::new("git")
+        .args(["count-objects", "-v"])
+        .current_dir(dir)
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git count-objects is failure [Exit code: {}]",
+            output.status
+        ));
+    }
+    Ok(parse_count_objects(&String::from_utf8(output.stdout)?))
+}
+
+fn parse_count_objects(s: &str) -> (u64, u64) {
+    let get = |key: &str| {
+        s.lines()
+            .filter_map(|l| l.strip_prefix(key))
+            .filter_map(|v| v.trim().parse().ok())
+            .next()
+            .unwrap_or(0)
+    };
+    (get("count:"), get("in-pack:"))
+}
+
+fn collect_garbage_by_command(repo: &Repository, mode: GcMode) -> Result<()> {
+    let scripts: Vec<Vec<String>> = match mode {
+        GcMode::Gc => vec![vec!["gc".into(), "--quiet".into()]],
+        GcMode::Repack => vec![vec![
+            "repack".into(),
+            "-a".into(),
+            "-d".into(),
+            "--quiet".into(),
+        ]],
+        GcMode::Shallow(depth) => vec![
+            vec![
+                "fetch".into(),
+                "--quiet".into(),
+                format!("--depth={}", depth),
+            ],
+            vec![
+                "reflog".into(),
+                "expire".into(),
+                "--expire=now".into(),
+                "--all".into(),
+            ],
+            vec!["gc".into(), "--quiet".into(), "--prune=now".into()],
+        ],
+    };
+    let config = crate::config::get()?;
+    for args in scripts {
+        // The shallow fetch talks to the remote, the rest is local
+        let output = if args[0] == "fetch" {
+            network::run(
+                network::command("git").args(&args).current_dir(&repo.dir),
+                config.timeout(repo),
+                config.retries(),
+            )?
+        } else {
+            crate::interrupt::output(
+                std::process::Command::new("git")
+                    .args(&args)
+                    .current_dir(&repo.dir),
+            )?
+        };
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git {} is failure [Exit code: {}] {}",
+                args[0],
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{git, temp_dir};
+
+    #[test]
+    fn parse_count_objects_ok() {
+        let data = "count: 12\nsize: 48\nin-pack: 3456\npacks: 1\nsize-pack: 789\n";
+        assert_eq!(parse_count_objects(data), (12, 3456));
+    }
+
+    #[test]
+    fn get_disk_usage_and_collect_garbage_ok() -> Result<()> {
+        let root = temp_dir("dotplugs_housekeeping");
+        let upstream = format!("{}/upstream", root);
+        std::fs::create_dir_all(&upstream)?;
+        git(&["init"], &upstream)?;
+        for i in 0..3 {
+            std::fs::write(format!("{}/file", upstream), format!("{}", i))?;
+            git(&["add", "file"], &upstream)?;
+            git(&["commit", "-m", &i.to_string()], &upstream)?;
+        }
+        git(&["clone", &format!("file://{}", upstream), "plugin"], &root)?;
+        let repo = Repository {
+            uri: upstream,
+            dir: format!("{}/plugin", root),
+            ..Default::default()
+        };
+
+        let usage = get_disk_usage(&repo)?;
+        assert!(!usage.shallow);
+        assert!(usage.git_size > 0);
+
+        let results = collect_garbage(&vec![repo.clone()], GcMode::Shallow(1))?;
+        assert!(results[0].error.is_none());
+        let usage = get_disk_usage(&repo)?;
+        assert!(usage.shallow);
+        assert_eq!(usage.loose_objects, 0);
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
+}
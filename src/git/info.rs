@@ -0,0 +1,53 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+
+pub fn get_last_commit_time(dir: &str) -> Result<NaiveDateTime> {
+    let repo = git2::Repository::open(dir)?;
+    let commit = repo.head()?.peel_to_commit()?;
+    Ok(chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+        .unwrap_or_default()
+        .naive_utc())
+}
+
+pub fn has_uncommitted_changes(dir: &str) -> Result<bool> {
+    let repo = git2::Repository::open(dir)?;
+    let mut opts = git2::StatusOptions::new();
+    opts.include_untracked(true).include_ignored(false);
+    let statuses = repo.statuses(Some(&mut opts))?;
+    Ok(!statuses.is_empty())
+}
+
+// "<short hash> <subject>" for each commit in old..new, newest first
+pub fn get_commit_subjects(dir: &str, old: &str, new: &str) -> Result<Vec<String>> {
+    let repo = git2::Repository::open(dir)?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(git2::Oid::from_str(new)?)?;
+    revwalk.hide(git2::Oid::from_str(old)?)?;
+    let mut subjects = vec![];
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        subjects.push(format!(
+            "{} {}",
+            &commit.id().to_string()[..7],
+            commit.summary().unwrap_or_default()
+        ));
+    }
+    Ok(subjects)
+}
+
+pub fn get_commit_time(dir: &str, commit: &str) -> Result<NaiveDateTime> {
+    let repo = git2::Repository::open(dir)?;
+    let commit = repo.find_commit(git2::Oid::from_str(commit)?)?;
+    Ok(chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+        .unwrap_or_default()
+        .naive_utc())
+}
+
+pub fn show_log(dir: &str, old: &str, new: &str) -> Result<()> {
+    std::process::Command::new("git")
+        .args(["log", "--patch", "--stat"])
+        .arg(format!("{}..{}", old, new))
+        .current_dir(dir)
+        .status()?;
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use crate::interrupt;
+use anyhow::Result;
+use log::warn;
+use std::process::{Command, Output};
+use std::time::Duration;
+
+const TRANSIENT_ERRORS: &[&str] = &[
+    "Could not resolve host",
+    "Connection timed out",
+    "Connection reset",
+    "Connection refused",
+    "Operation timed out",
+    "Temporary failure",
+    "The remote end hung up unexpectedly",
+    "early EOF",
+    "RPC failed",
+    "HTTP 502",
+    "HTTP 503",
+    "HTTP 504",
+];
+
+// Nothing may wait for a password or host-key confirmation
+pub fn command(program: &str) -> Command {
+    let mut cmd = Command::new(program);
+    cmd.env("GIT_TERMINAL_PROMPT", "0");
+    if std::env::var_os("GIT_SSH_COMMAND").is_none() && std::env::var_os("GIT_SSH").is_none() {
+        cmd.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+    }
+    cmd
+}
+
+pub fn run(cmd: &mut Command, timeout: Duration, retries: u32) -> Result<Output> {
+    let mut attempt = 0;
+    loop {
+        let output = interrupt::output_with_timeout(cmd, timeout)?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.success() || attempt >= retries || !is_transient(&stderr) {
+            return Ok(output);
+        }
+        attempt += 1;
+        let wait = Duration::from_secs(1 << attempt);
+        warn!(
+            "{} (retry {}/{} in {}s)",
+            stderr.trim(),
+            attempt,
+            retries,
+            wait.as_secs()
+        );
+        std::thread::sleep(wait);
+        interrupt::check()?;
+    }
+}
+
+fn is_transient(stderr: &str) -> bool {
+    TRANSIENT_ERRORS.iter().any(|e| stderr.contains(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn run_retries_transient_errors() -> Result<()> {
+        let counter = temp_dir("dotplugs_network");
+        let script = format!(
+            "echo x >> {0}; [ $(wc -l < {0}) -ge 2 ] || {{ echo 'fatal: early EOF' >&2; exit 1; }}",
+            counter
+        );
+        let output = run(
+            command("sh").args(["-c", &script]),
+            Duration::from_secs(10),
+            2,
+        )?;
+        assert!(output.status.success());
+        assert_eq!(std::fs::read_to_string(&counter)?.lines().count(), 2);
+
+        let output = run(
+            command("sh").args(["-c", "echo 'fatal: not a git repository' >&2; exit 128"]),
+            Duration::from_secs(10),
+            2,
+        )?;
+        assert!(!output.status.success());
+        std::fs::remove_file(&counter).unwrap_or(());
+        Ok(())
+    }
+}
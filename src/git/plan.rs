@@ -0,0 +1,157 @@
+use crate::git::GitStatus;
+use crate::git::SubmoduleState;
+use crate::git::UpdateStatus;
+use crate::repository::Manager;
+use anyhow::Result;
+use log::warn;
+use serde::Serialize;
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+    Pull,
+    CheckoutTag,
+    SubmoduleUpdate,
+    FollowDefaultBranch,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UpdatePlan {
+    pub uri: String,
+    pub dir: String,
+    pub strategy: Strategy,
+    pub old_commit: String,
+    pub new_commit: String,
+    pub commits: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub submodules: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manager: Option<Manager>,
+    pub hooks: Vec<String>,
+}
+
+pub fn plan_updates(statuses: &[GitStatus], follow_default_branch: bool) -> Vec<UpdatePlan> {
+    statuses
+        .iter()
+        .filter_map(|s| match plan_update(s, follow_default_branch) {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("{}: {:?}", &s.uri, e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn plan_update(status: &GitStatus, follow_default_branch: bool) -> Result<Option<UpdatePlan>> {
+    let (strategy, target, refname) = match (&status.status, &status.latest_tag) {
+        (UpdateStatus::Required, Some(tag)) => (
+            Strategy::CheckoutTag,
+            Some(tag.to_string()),
+            format!("refs/tags/{}", tag),
+        ),
+        (UpdateStatus::Required, None) => (
+            Strategy::Pull,
+            Some(format!("origin/{}", status.branch)),
+            format!("refs/remotes/origin/{}", status.branch),
+        ),
+        (UpdateStatus::SubmoduleRequired, _) => {
+            (Strategy::SubmoduleUpdate, None, "HEAD".to_string())
+        }
+        (UpdateStatus::DefaultBranchChanged, _) | (UpdateStatus::UpstreamGone, _)
+            if follow_default_branch =>
+        {
+            match &status.default_branch {
+                Some(b) => (
+                    Strategy::FollowDefaultBranch,
+                    Some(b.to_string()),
+                    format!("refs/remotes/origin/{}", b),
+                ),
+                None => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    let repo = git2::Repository::open(&status.dir)?;
+    let old = repo.revparse_single("HEAD")?.peel_to_commit()?.id();
+    let new = repo.revparse_single(&refname)?.peel_to_commit()?.id();
+    let (commits, _) = repo.graph_ahead_behind(new, old)?;
+    let subject = repo.find_commit(new)?.summary().map(|s| s.to_string());
+    let submodules = match strategy {
+        Strategy::SubmoduleUpdate => status
+            .submodules
+            .iter()
+            .filter(|s| s.state != SubmoduleState::UpstreamMoved)
+            .map(|s| s.path.to_string())
+            .collect(),
+        _ => vec![],
+    };
+    Ok(Some(UpdatePlan {
+        uri: status.uri.to_string(),
+        dir: status.dir.to_string(),
+        strategy,
+        old_commit: old.to_string(),
+        new_commit: new.to_string(),
+        commits,
+        subject,
+        target,
+        submodules,
+        manager: status.manager,
+        hooks: vec![],
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{git, temp_dir};
+
+    #[test]
+    fn plan_updates_ok() -> Result<()> {
+        let root = temp_dir("dotplugs_plan");
+        let upstream = format!("{}/upstream", root);
+        let dir = format!("{}/plugin", root);
+        std::fs::create_dir_all(&upstream)?;
+        git(&["init"], &upstream)?;
+        git(&["commit", "--allow-empty", "-m", "first"], &upstream)?;
+        git(&["clone", "--quiet", &upstream, "plugin"], &root)?;
+        git(&["commit", "--allow-empty", "-m", "second"], &upstream)?;
+        git(&["commit", "--allow-empty", "-m", "third"], &upstream)?;
+        git(&["fetch", "--quiet"], &dir)?;
+
+        let status = GitStatus {
+            uri: upstream.to_string(),
+            dir: dir.to_string(),
+            branch: "master".to_string(),
+            status: UpdateStatus::Required,
+            submodules: vec![],
+            track: Default::default(),
+            current_tag: None,
+            latest_tag: None,
+            default_branch: None,
+            manager: None,
+            attention: vec![],
+        };
+        let mut already = status.clone();
+        already.status = UpdateStatus::Already;
+        let plans = plan_updates(&[status, already], false);
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].strategy, Strategy::Pull);
+        assert_eq!(plans[0].commits, 2);
+        assert_eq!(plans[0].subject, Some("third".to_string()));
+        assert_ne!(plans[0].old_commit, plans[0].new_commit);
+
+        let head = git2::Repository::open(&dir)?
+            .revparse_single("HEAD")?
+            .id()
+            .to_string();
+        assert_eq!(head, plans[0].old_commit);
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
+}
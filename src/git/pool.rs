@@ -0,0 +1,40 @@
+use anyhow::Result;
+use futures::executor;
+use futures::task::SpawnExt;
+use std::sync::Arc;
+
+// Items that were not started before Ctrl-C are returned as None
+pub fn map_in_pool<T, R, F>(items: Vec<T>, f: F) -> Result<Vec<Option<R>>>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let _busy = crate::interrupt::busy();
+    let pool = executor::ThreadPool::new()?;
+    let f = Arc::new(f);
+    let mut futures = vec![];
+    for item in items {
+        let f = Arc::clone(&f);
+        futures.push(pool.spawn_with_handle(async move {
+            if crate::interrupt::is_interrupted() {
+                None
+            } else {
+                Some(f(item))
+            }
+        })?);
+    }
+    Ok(executor::block_on(futures::future::join_all(futures)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_in_pool_keeps_order() -> Result<()> {
+        let results = map_in_pool((0..32).collect(), |i: i32| i * 2)?;
+        assert_eq!(results, (0..32).map(|i| Some(i * 2)).collect::<Vec<_>>());
+        Ok(())
+    }
+}
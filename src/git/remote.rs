@@ -0,0 +1,65 @@
+use crate::repository::RemoteUrl;
+use anyhow::{anyhow, Result};
+
+// Points origin at the new name, keeping the protocol and user the checkout already uses.
+// Returns the new url, or None when origin already points there.
+pub fn set_remote_name_with_owner(dir: &str, name_with_owner: &str) -> Result<Option<String>> {
+    let repo = git2::Repository::open(dir)?;
+    let old = repo
+        .find_remote("origin")?
+        .url()
+        .ok_or(anyhow!("origin url is not utf-8"))?
+        .to_string();
+    let remote = RemoteUrl::parse(&old)?;
+    if remote
+        .name_with_owner()
+        .eq_ignore_ascii_case(name_with_owner)
+    {
+        return Ok(None);
+    }
+    let mut new = remote.with_name_with_owner(name_with_owner).url();
+    if !old.trim_end_matches('/').ends_with(".git") {
+        new = new.trim_end_matches(".git").to_string();
+    }
+    repo.remote_set_url("origin", &new)?;
+    Ok(Some(new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{git, temp_dir};
+
+    #[test]
+    fn set_remote_name_with_owner_ok() -> Result<()> {
+        let dir = temp_dir("dotplugs_remote");
+        std::fs::create_dir_all(&dir)?;
+        git(&["init"], &dir)?;
+        git(
+            &["remote", "add", "origin", "git@github.com:owner/old.git"],
+            &dir,
+        )?;
+
+        let url = set_remote_name_with_owner(&dir, "newowner/new")?;
+        assert_eq!(url.as_deref(), Some("git@github.com:newowner/new.git"));
+        assert_eq!(set_remote_name_with_owner(&dir, "NewOwner/new")?, None);
+
+        git(
+            &[
+                "remote",
+                "set-url",
+                "origin",
+                "https://github.com/owner/old",
+            ],
+            &dir,
+        )?;
+        set_remote_name_with_owner(&dir, "newowner/new")?;
+        let repo = git2::Repository::open(&dir)?;
+        assert_eq!(
+            repo.find_remote("origin")?.url(),
+            Some("https://github.com/newowner/new")
+        );
+        std::fs::remove_dir_all(&dir).unwrap_or(());
+        Ok(())
+    }
+}
@@ -1,15 +1,19 @@
 use crate::git::branch;
 use crate::git::fetch;
+use crate::git::pool;
+use crate::git::submodule;
+use crate::git::tag;
 use crate::git::GitStatus;
+use crate::git::SubmoduleState;
 use crate::git::UpdateStatus;
+use crate::interrupt::TimedOut;
 use crate::repository::Repositories;
 use crate::repository::Repository;
-use anyhow::{anyhow, Result};
-use futures::executor;
-use futures::task::SpawnExt;
+use crate::repository::Tracking;
+use anyhow::Result;
+use colored::Colorize;
 use log::{debug, warn};
 use std::io::Write;
-use std::sync::{Arc, Mutex};
 use termion::clear;
 
 #[allow(dead_code)]
@@ -29,57 +33,171 @@ pub fn get_status_sync(repos: &Repositories) -> Result<Vec<GitStatus>> {
 }
 
 pub fn get_status_async(repos: &Repositories) -> Result<Vec<GitStatus>> {
-    let git_statuses = Arc::new(Mutex::new(Vec::<GitStatus>::new()));
-    let pool = executor::ThreadPool::new()?;
-    let mut futures = vec![];
-    for repo in repos.clone() {
-        let git_statuses = Arc::clone(&git_statuses);
-        let future = async move {
-            eprint!("\r{}Checking: {}", clear::CurrentLine, repo.uri);
-            std::io::stdout().flush().unwrap();
-            match get_status_after_fetch(&repo) {
-                Ok(sts) => git_statuses.lock().unwrap().push(sts),
-                Err(e) => {
-                    warn!("\r{}", clear::CurrentLine);
-                    warn!("{:?}: {:?}", &repo, e);
-                }
+    let git_statuses = pool::map_in_pool(repos.clone(), |repo| {
+        eprint!("\r{}Checking: {}", clear::CurrentLine, repo.uri);
+        std::io::stdout().flush().unwrap();
+        match get_status_after_fetch(&repo) {
+            Ok(sts) => Some(sts),
+            Err(e) => {
+                warn!("\r{}", clear::CurrentLine);
+                warn!("{:?}: {:?}", &repo, e);
+                None
             }
-        };
-        futures.push(pool.spawn_with_handle(future)?);
-    }
-    executor::block_on(futures::future::join_all(futures));
-    let g = Arc::try_unwrap(git_statuses).map_err(|e| anyhow!("Async Error {:?}", e))?;
+        }
+    })?;
     eprint!("\r{}", clear::CurrentLine);
-    Ok(g.into_inner()?)
+    for (repo, _) in repos.iter().zip(&git_statuses).filter(|(_, s)| s.is_none()) {
+        eprintln!("{} {}", repo.uri, "Not checked (interrupted)".yellow());
+    }
+    Ok(git_statuses.into_iter().flatten().flatten().collect())
 }
 
 fn get_status_after_fetch(repo: &Repository) -> Result<GitStatus> {
+    if !std::path::Path::new(&repo.dir).exists() {
+        return Ok(get_status_without_git(repo, UpdateStatus::NotInstalled));
+    }
     let git_repo = match git2::Repository::open(&repo.dir) {
         Ok(x) => x,
-        Err(_) => {
-            return Ok(GitStatus {
-                uri: repo.uri.to_string(),
-                dir: repo.dir.to_string(),
-                branch: "master".to_string(),
-                status: UpdateStatus::NotGitRepository,
-            })
+        Err(_) => return Ok(get_status_without_git(repo, UpdateStatus::NotGitRepository)),
+    };
+    if let Err(e) = fetch::fetch_repository(repo) {
+        if e.is::<TimedOut>() {
+            warn!("{}: fetch {}", repo.uri, e);
+            return Ok(get_status_without_git(repo, UpdateStatus::TimedOut));
+        }
+        return Err(e);
+    }
+    let submodules = submodule::get_submodule_statuses(&git_repo)?;
+    let latest_tag = tag::get_latest_tag(&git_repo, &repo.track)?;
+    if latest_tag.is_none() && repo.track != Tracking::Branch {
+        warn!(
+            "{}: no tag matches {:?}, tracking branch",
+            repo.uri, repo.track
+        );
+    }
+    let mut default_branch = None;
+    let (mut status, branch) = match &latest_tag {
+        Some(t) => (
+            get_tag_update_status(&git_repo, t, &repo.track)?,
+            branch::get_current_branch(&git_repo).unwrap_or_else(|_| "HEAD".to_string()),
+        ),
+        None => {
+            let branch = branch::get_current_branch(&git_repo)?;
+            // The remote's default branch matters when ours was the default at clone time
+            // (upstream may have moved HEAD and kept the old branch) or when ours is gone
+            let was_default =
+                branch::get_local_default_branch(&git_repo).as_deref() == Some(&branch);
+            if was_default
+                || git_repo
+                    .revparse_single(&format!("origin/{}", branch))
+                    .is_err()
+            {
+                default_branch = match fetch::get_remote_default_branch(repo) {
+                    Err(e) if e.is::<TimedOut>() => {
+                        warn!("{}: ls-remote {}", repo.uri, e);
+                        return Ok(get_status_without_git(repo, UpdateStatus::TimedOut));
+                    }
+                    x => x?,
+                };
+            }
+            (
+                get_update_status(&git_repo, default_branch.as_deref())?,
+                branch,
+            )
         }
     };
-    fetch::fetch_repository(repo)?;
+    // A newer upstream commit of a submodule is only reported; update keeps the recorded gitlink
+    if status == UpdateStatus::Already
+        && submodules
+            .iter()
+            .any(|s| s.state != SubmoduleState::UpstreamMoved)
+    {
+        status = UpdateStatus::SubmoduleRequired;
+    }
+    let current_tag = match &latest_tag {
+        Some(_) => tag::get_current_tag(&git_repo, &repo.track)?.map(|t| t.name),
+        None => None,
+    };
     Ok(GitStatus {
         uri: repo.uri.to_string(),
         dir: repo.dir.to_string(),
-        branch: branch::get_current_branch(&git_repo)?,
-        status: get_update_status(&git_repo)?,
+        branch,
+        status,
+        submodules,
+        track: repo.track.clone(),
+        current_tag,
+        latest_tag: latest_tag.map(|t| t.name),
+        default_branch,
+        manager: repo.manager,
+        attention: vec![],
     })
 }
 
-fn get_update_status(repo: &git2::Repository) -> Result<UpdateStatus> {
+fn get_status_without_git(repo: &Repository, status: UpdateStatus) -> GitStatus {
+    GitStatus {
+        uri: repo.uri.to_string(),
+        dir: repo.dir.to_string(),
+        branch: repo.branch.clone().unwrap_or_else(|| "master".to_string()),
+        status,
+        submodules: vec![],
+        track: repo.track.clone(),
+        current_tag: None,
+        latest_tag: None,
+        default_branch: None,
+        manager: repo.manager,
+        attention: vec![],
+    }
+}
+
+fn get_tag_update_status(
+    repo: &git2::Repository,
+    latest_tag: &tag::Tag,
+    track: &Tracking,
+) -> Result<UpdateStatus> {
+    let local_hash = repo.revparse_single("HEAD")?.peel_to_commit()?.id();
+    debug!(
+        "local_hash: {:?} latest_tag: {} {:?}",
+        &local_hash, &latest_tag.name, &latest_tag.commit
+    );
+    if local_hash == latest_tag.commit {
+        return Ok(UpdateStatus::Already);
+    }
+    // Never check out an older tag than HEAD unless the user pinned that tag
+    if !track.is_pinned()
+        && repo
+            .graph_descendant_of(local_hash, latest_tag.commit)
+            .unwrap_or(false)
+    {
+        return Ok(UpdateStatus::Already);
+    }
+    Ok(UpdateStatus::Required)
+}
+
+fn get_update_status(
+    repo: &git2::Repository,
+    remote_default_branch: Option<&str>,
+) -> Result<UpdateStatus> {
     let branch_name = branch::get_current_branch(&repo)?;
     let remote_branch_name = format!("origin/{}", &branch_name);
 
+    if let (Some(remote_default), Some(local_default)) = (
+        remote_default_branch,
+        branch::get_local_default_branch(repo),
+    ) {
+        debug!(
+            "local default: {} remote default: {}",
+            &local_default, &remote_default
+        );
+        if remote_default != local_default && branch_name == local_default {
+            return Ok(UpdateStatus::DefaultBranchChanged);
+        }
+    }
+
     let local_hash = repo.revparse_single("HEAD")?.id();
-    let remote_hash = repo.revparse_single(&remote_branch_name)?.id();
+    let remote_hash = match repo.revparse_single(&remote_branch_name) {
+        Ok(x) => x.id(),
+        Err(_) => return Ok(UpdateStatus::UpstreamGone),
+    };
     // let remote_hash = repo.revparse_single("FETCH_HEAD")?.id();
     debug!(
         "local_hash: {:?} remote_hash: {:?}",
@@ -88,6 +206,18 @@ fn get_update_status(repo: &git2::Repository) -> Result<UpdateStatus> {
     if local_hash == remote_hash {
         return Ok(UpdateStatus::Already);
     }
+    if repo
+        .graph_descendant_of(local_hash, remote_hash)
+        .unwrap_or(false)
+    {
+        return Ok(UpdateStatus::Already);
+    }
+    if !repo
+        .graph_descendant_of(remote_hash, local_hash)
+        .unwrap_or(true)
+    {
+        return Ok(UpdateStatus::ForcePushed);
+    }
     Ok(UpdateStatus::Required)
 }
 
@@ -95,6 +225,7 @@ fn get_update_status(repo: &git2::Repository) -> Result<UpdateStatus> {
 mod tests {
     use super::*;
     use crate::repository::Repository;
+    use crate::test_util::{git, temp_dir};
     extern crate pretty_env_logger;
     use anyhow::{anyhow, Result};
 
@@ -134,9 +265,10 @@ mod tests {
         let repo = Repository {
             uri: "https://github.com/octocat/Spoon-Knife".to_string(),
             dir: target_git_dir.to_string(),
+            ..Default::default()
         };
         let git_repo = git2::Repository::open(&repo.dir).unwrap();
-        let n = get_update_status(&git_repo)?;
+        let n = get_update_status(&git_repo, None)?;
         assert_eq!(n, UpdateStatus::Required);
         std::fs::remove_dir_all(&target_git_dir).unwrap_or(());
         Ok(())
@@ -154,6 +286,7 @@ mod tests {
         let repo = Repository {
             uri: "https://github.com/octocat/Spoon-Knife".to_string(),
             dir: target_git_dir.to_string(),
+            ..Default::default()
         };
         let git_repo = git2::Repository::clone(&repo.uri, &repo.dir).unwrap();
         git_repo
@@ -164,9 +297,96 @@ mod tests {
                 "",
             )
             .unwrap();
-        let n = get_update_status(&git_repo)?;
+        let n = get_update_status(&git_repo, None)?;
         assert_eq!(n, UpdateStatus::Already);
         std::fs::remove_dir_all(&target_git_dir).unwrap_or(());
         Ok(())
     }
+
+    fn create_local_clone() -> Result<(String, Repository)> {
+        let root = temp_dir("dotplugs_status");
+        let upstream = format!("{}/upstream", root);
+        std::fs::create_dir_all(&upstream)?;
+        git(&["init"], &upstream)?;
+        git(&["commit", "--allow-empty", "-m", "first"], &upstream)?;
+        git(&["commit", "--allow-empty", "-m", "second"], &upstream)?;
+        git(&["clone", "upstream", "plugin"], &root)?;
+        let repo = Repository {
+            uri: upstream,
+            dir: format!("{}/plugin", root),
+            ..Default::default()
+        };
+        Ok((root, repo))
+    }
+
+    #[test]
+    fn get_status_of_default_branch_changed() -> Result<()> {
+        init();
+        let (root, repo) = create_local_clone()?;
+        git(&["branch", "-m", "master", "main"], &repo.uri)?;
+        let status = get_status_after_fetch(&repo)?;
+        assert_eq!(status.status, UpdateStatus::DefaultBranchChanged);
+        assert_eq!(status.default_branch, Some("main".to_string()));
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
+
+    #[test]
+    fn get_status_of_default_branch_changed_keeping_old_branch() -> Result<()> {
+        init();
+        let (root, repo) = create_local_clone()?;
+        git(&["checkout", "-b", "main"], &repo.uri)?;
+        git(&["commit", "--allow-empty", "-m", "third"], &repo.uri)?;
+        let status = get_status_after_fetch(&repo)?;
+        assert_eq!(status.status, UpdateStatus::DefaultBranchChanged);
+        assert_eq!(status.default_branch, Some("main".to_string()));
+
+        // Still on the old default but upstream HEAD has not moved: nothing to report
+        git(&["checkout", "master"], &repo.uri)?;
+        let status = get_status_after_fetch(&repo)?;
+        assert_eq!(status.status, UpdateStatus::Already);
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
+
+    #[test]
+    fn get_status_of_upstream_gone() -> Result<()> {
+        init();
+        let (root, repo) = create_local_clone()?;
+        git(&["checkout", "-b", "feature"], &repo.dir)?;
+        git(&["push", "-u", "origin", "feature"], &repo.dir)?;
+        git(&["branch", "-D", "feature"], &repo.uri)?;
+        let status = get_status_after_fetch(&repo)?;
+        assert_eq!(status.status, UpdateStatus::UpstreamGone);
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
+
+    #[test]
+    fn get_status_of_tag_behind_head() -> Result<()> {
+        init();
+        let (root, mut repo) = create_local_clone()?;
+        git(&["tag", "v1.0.0", "HEAD^"], &repo.uri)?;
+        repo.track = Tracking::LatestTag;
+        assert_eq!(get_status_after_fetch(&repo)?.status, UpdateStatus::Already);
+        repo.track = Tracking::TagPattern("v1.0.0".to_string());
+        assert_eq!(
+            get_status_after_fetch(&repo)?.status,
+            UpdateStatus::Required
+        );
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
+
+    #[test]
+    fn get_status_of_force_pushed() -> Result<()> {
+        init();
+        let (root, repo) = create_local_clone()?;
+        git(&["reset", "--hard", "HEAD^"], &repo.uri)?;
+        git(&["commit", "--allow-empty", "-m", "rewritten"], &repo.uri)?;
+        let status = get_status_after_fetch(&repo)?;
+        assert_eq!(status.status, UpdateStatus::ForcePushed);
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
 }
@@ -0,0 +1,126 @@
+use crate::git::SubmoduleState;
+use crate::git::SubmoduleStatus;
+use anyhow::{anyhow, Result};
+use log::debug;
+
+pub fn get_submodule_statuses(repo: &git2::Repository) -> Result<Vec<SubmoduleStatus>> {
+    let mut statuses = vec![];
+    for sm in repo.submodules()? {
+        let name = sm.name().ok_or(anyhow!("convert error"))?.to_string();
+        let path = sm
+            .path()
+            .to_str()
+            .ok_or(anyhow!("convert error"))?
+            .to_string();
+        if let Some(state) = get_submodule_state(repo, &sm)? {
+            debug!("submodule {} is {:?}", &path, &state);
+            statuses.push(SubmoduleStatus { name, path, state });
+        }
+    }
+    Ok(statuses)
+}
+
+fn get_submodule_state(
+    repo: &git2::Repository,
+    sm: &git2::Submodule,
+) -> Result<Option<SubmoduleState>> {
+    let name = sm.name().ok_or(anyhow!("convert error"))?;
+    let status = repo.submodule_status(name, git2::SubmoduleIgnore::None)?;
+    if status.is_wd_uninitialized() || sm.workdir_id().is_none() {
+        return Ok(Some(SubmoduleState::Uninitialized));
+    }
+    let recorded = match sm.head_id() {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    if sm.workdir_id() != Some(recorded) {
+        return Ok(Some(SubmoduleState::OutOfSync));
+    }
+    if is_upstream_moved(sm, recorded)? {
+        return Ok(Some(SubmoduleState::UpstreamMoved));
+    }
+    Ok(None)
+}
+
+fn is_upstream_moved(sm: &git2::Submodule, recorded: git2::Oid) -> Result<bool> {
+    let sub_repo = sm.open()?;
+    let remote_branch_name = match sm.branch() {
+        Some(b) => format!("origin/{}", b),
+        None => "origin/HEAD".to_string(),
+    };
+    let remote_hash = match sub_repo.revparse_single(&remote_branch_name) {
+        Ok(x) => x.peel_to_commit()?.id(),
+        Err(_) => return Ok(false),
+    };
+    debug!(
+        "submodule recorded: {:?} remote_hash: {:?}",
+        &recorded, &remote_hash
+    );
+    if remote_hash == recorded {
+        return Ok(false);
+    }
+    Ok(sub_repo.graph_descendant_of(remote_hash, recorded)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{git, temp_dir};
+
+    fn init() {
+        let _ = pretty_env_logger::formatted_builder()
+            .is_test(true)
+            .parse_filters("DEBUG")
+            .try_init();
+    }
+
+    fn create_superproject() -> Result<(String, String)> {
+        let root = temp_dir("dotplugs_submodule");
+        let lib = format!("{}/lib", root);
+        let parent = format!("{}/parent", root);
+        std::fs::create_dir_all(&lib)?;
+        std::fs::create_dir_all(&parent)?;
+        git(&["init"], &lib)?;
+        git(&["commit", "--allow-empty", "-m", "lib"], &lib)?;
+        git(&["init"], &parent)?;
+        git(&["submodule", "add", &lib, "vendor/lib"], &parent)?;
+        git(&["commit", "-m", "add submodule"], &parent)?;
+        Ok((root, lib))
+    }
+
+    #[test]
+    fn get_submodule_statuses_of_uninitialized() -> Result<()> {
+        init();
+        let (root, _) = create_superproject()?;
+        let plugin = format!("{}/plugin", root);
+        git(&["clone", "parent", "plugin"], &root)?;
+        let git_repo = git2::Repository::open(&plugin)?;
+        let statuses = get_submodule_statuses(&git_repo)?;
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].path, "vendor/lib");
+        assert_eq!(statuses[0].state, SubmoduleState::Uninitialized);
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
+
+    #[test]
+    fn get_submodule_statuses_of_upstream_moved() -> Result<()> {
+        init();
+        let (root, lib) = create_superproject()?;
+        let plugin = format!("{}/plugin", root);
+        git(
+            &["clone", "--recurse-submodules", "parent", "plugin"],
+            &root,
+        )?;
+        let git_repo = git2::Repository::open(&plugin)?;
+        assert!(get_submodule_statuses(&git_repo)?.is_empty());
+
+        git(&["commit", "--allow-empty", "-m", "lib update"], &lib)?;
+        git(&["fetch"], &format!("{}/vendor/lib", plugin))?;
+        let statuses = get_submodule_statuses(&git_repo)?;
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, SubmoduleState::UpstreamMoved);
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
+}
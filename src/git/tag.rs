@@ -0,0 +1,159 @@
+use crate::repository::Tracking;
+use anyhow::Result;
+use log::debug;
+
+pub struct Tag {
+    pub name: String,
+    pub commit: git2::Oid,
+}
+
+pub fn get_latest_tag(repo: &git2::Repository, track: &Tracking) -> Result<Option<Tag>> {
+    let mut tags = get_tracked_tags(repo, track)?;
+    tags.sort_by(|a, b| compare_tag_names(&a.name, &b.name));
+    Ok(tags.pop())
+}
+
+pub fn get_current_tag(repo: &git2::Repository, track: &Tracking) -> Result<Option<Tag>> {
+    let head = repo.revparse_single("HEAD")?.peel_to_commit()?.id();
+    let mut tags: Vec<Tag> = get_tracked_tags(repo, track)?
+        .into_iter()
+        .filter(|t| t.commit == head)
+        .collect();
+    tags.sort_by(|a, b| compare_tag_names(&a.name, &b.name));
+    Ok(tags.pop())
+}
+
+// Any version tag at HEAD, whatever the plugin tracks
+pub fn get_head_tag(dir: &str) -> Result<Option<String>> {
+    let repo = git2::Repository::open(dir)?;
+    Ok(get_current_tag(&repo, &Tracking::LatestTag)?.map(|t| t.name))
+}
+
+// Names of the tags on commits that an update from `old` to `new` brings in, `new` included
+pub fn get_tags_between(dir: &str, old: &str, new: &str) -> Result<Vec<String>> {
+    let repo = git2::Repository::open(dir)?;
+    let old = git2::Oid::from_str(old)?;
+    let new = git2::Oid::from_str(new)?;
+    let mut names = vec![];
+    for name in repo.tag_names(None)?.iter().flatten() {
+        let commit = match repo
+            .revparse_single(&format!("refs/tags/{}", name))
+            .and_then(|o| o.peel_to_commit())
+        {
+            Ok(c) => c.id(),
+            Err(_) => continue,
+        };
+        if commit != old
+            && repo.graph_descendant_of(commit, old)?
+            && (commit == new || repo.graph_descendant_of(new, commit)?)
+        {
+            names.push(name.to_string());
+        }
+    }
+    Ok(names)
+}
+
+fn get_tracked_tags(repo: &git2::Repository, track: &Tracking) -> Result<Vec<Tag>> {
+    let pattern = match track {
+        Tracking::Branch => return Ok(vec![]),
+        Tracking::LatestTag => None,
+        Tracking::TagPattern(p) => Some(glob::Pattern::new(p)?),
+    };
+    let mut tags = vec![];
+    for name in repo.tag_names(None)?.iter().flatten() {
+        let is_tracked = match &pattern {
+            Some(p) => p.matches(name),
+            None => parse_version(name).is_some_and(|v| !v.is_prerelease()),
+        };
+        if !is_tracked {
+            continue;
+        }
+        // Tags of trees or blobs cannot be checked out as a plugin version
+        let commit = match repo
+            .revparse_single(&format!("refs/tags/{}", name))
+            .and_then(|o| o.peel_to_commit())
+        {
+            Ok(c) => c.id(),
+            Err(e) => {
+                debug!("skipping tag {}: {}", name, e);
+                continue;
+            }
+        };
+        tags.push(Tag {
+            name: name.to_string(),
+            commit,
+        });
+    }
+    debug!("tracked tags: {}", tags.len());
+    Ok(tags)
+}
+
+fn parse_version(name: &str) -> Option<semver::Version> {
+    semver::Version::parse(name.trim_start_matches('v')).ok()
+}
+
+fn compare_tag_names(a: &str, b: &str) -> std::cmp::Ordering {
+    match (parse_version(a), parse_version(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Greater,
+        (None, Some(_)) => std::cmp::Ordering::Less,
+        (None, None) => a.cmp(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{git, temp_dir};
+
+    #[test]
+    fn get_latest_tag_ok() -> Result<()> {
+        let target_git_dir = temp_dir("dotplugs_tag");
+        std::fs::create_dir_all(&target_git_dir)?;
+        git(&["init"], &target_git_dir)?;
+        for tag in &["v1.4.2", "v1.10.0", "v2.0.0-beta.1", "nightly"] {
+            git(&["commit", "--allow-empty", "-m", tag], &target_git_dir)?;
+            git(&["tag", tag], &target_git_dir)?;
+        }
+        git(&["checkout", "v1.4.2"], &target_git_dir)?;
+
+        let git_repo = git2::Repository::open(&target_git_dir)?;
+        // A tag pointing at a tree is ignored instead of failing the status
+        let tree = git_repo.revparse_single("HEAD^{tree}")?;
+        git_repo.tag_lightweight("v3.0.0", &tree, false)?;
+        let latest = get_latest_tag(&git_repo, &Tracking::LatestTag)?.unwrap();
+        assert_eq!(latest.name, "v1.10.0");
+        let current = get_current_tag(&git_repo, &Tracking::LatestTag)?.unwrap();
+        assert_eq!(current.name, "v1.4.2");
+        let pattern = Tracking::TagPattern("v1.4.*".to_string());
+        let latest = get_latest_tag(&git_repo, &pattern)?.unwrap();
+        assert_eq!(latest.name, "v1.4.2");
+        assert!(get_latest_tag(&git_repo, &Tracking::Branch)?.is_none());
+        std::fs::remove_dir_all(&target_git_dir).unwrap_or(());
+        Ok(())
+    }
+
+    #[test]
+    fn get_tags_between_ok() -> Result<()> {
+        let dir = temp_dir("dotplugs_tag");
+        std::fs::create_dir_all(&dir)?;
+        git(&["init"], &dir)?;
+        for tag in &["v1.0.0", "v1.1.0", "release-2", "v1.2.0", "v1.3.0"] {
+            git(&["commit", "--allow-empty", "-m", tag], &dir)?;
+            git(&["tag", tag], &dir)?;
+        }
+        let git_repo = git2::Repository::open(&dir)?;
+        let commit = |t: &str| -> Result<String> {
+            Ok(git_repo
+                .revparse_single(t)?
+                .peel_to_commit()?
+                .id()
+                .to_string())
+        };
+        let mut tags = get_tags_between(&dir, &commit("v1.0.0")?, &commit("v1.2.0")?)?;
+        tags.sort();
+        assert_eq!(tags, vec!["release-2", "v1.1.0", "v1.2.0"]);
+        std::fs::remove_dir_all(&dir).unwrap_or(());
+        Ok(())
+    }
+}
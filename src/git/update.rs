@@ -1,28 +1,241 @@
+use crate::git::network;
+use crate::git::pool;
 use crate::git::GitStatus;
 use crate::git::UpdateStatus;
-use anyhow::Result;
-use std::process::Child;
-
-pub fn update_repositories(statuses: &Vec<GitStatus>) -> Result<()> {
-    let mut children = vec![];
-    for status in statuses {
-        if status.status == UpdateStatus::Required {
-            children.push(update_repository_by_command(&status.dir)?);
+use crate::interrupt;
+use crate::interrupt::TimedOut;
+use crate::repository::Manager;
+use anyhow::{anyhow, Result};
+use log::warn;
+use std::process::{Command, Output};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct UpdateResult {
+    pub uri: String,
+    pub dir: String,
+    pub manager: Option<Manager>,
+    pub old_rev: String,
+    pub new_rev: String,
+    pub success: bool,
+    pub timed_out: bool,
+}
+
+pub fn update_repositories(
+    statuses: &[GitStatus],
+    follow_default_branch: bool,
+) -> Result<Vec<UpdateResult>> {
+    let config = crate::config::get()?;
+    let jobs: Vec<_> = statuses
+        .iter()
+        .filter(|s| {
+            s.status.needs_update()
+                || (follow_default_branch && s.status.needs_repair() && s.default_branch.is_some())
+        })
+        .map(|s| (s.clone(), config.timeout(&s.repository())))
+        .collect();
+    let retries = config.retries();
+    let results = pool::map_in_pool(jobs, move |(status, timeout)| {
+        let old_rev = get_head_rev(&status.dir);
+        let (success, timed_out) = match update_repository(&status, timeout, retries) {
+            Ok(success) => (success, false),
+            Err(e) => {
+                warn!("{}: {}", &status.uri, e);
+                (false, e.is::<TimedOut>())
+            }
+        };
+        UpdateResult {
+            uri: status.uri.to_string(),
+            dir: status.dir.to_string(),
+            manager: status.manager,
+            new_rev: get_head_rev(&status.dir),
+            old_rev,
+            success,
+            timed_out,
+        }
+    })?;
+    Ok(results.into_iter().flatten().collect())
+}
+
+fn update_repository(status: &GitStatus, timeout: Duration, retries: u32) -> Result<bool> {
+    let output = match (&status.status, &status.latest_tag, &status.default_branch) {
+        (UpdateStatus::Required, Some(tag), _) => {
+            eprintln!("Update: {} -> {}", &status.uri, tag);
+            run_steps(checkout_tag_steps(&status.dir, tag), timeout, retries)?
+        }
+        (UpdateStatus::Required, None, _) => {
             eprintln!("Update: {}", &status.uri);
+            network::run(
+                &mut update_repository_by_command(&status.dir),
+                timeout,
+                retries,
+            )?
+        }
+        (UpdateStatus::SubmoduleRequired, _, _) => {
+            eprintln!("Update submodules: {}", &status.uri);
+            return update_submodules_by_command(status, timeout, retries);
+        }
+        (_, _, Some(default_branch)) => {
+            eprintln!(
+                "Update: {} {} -> {}",
+                &status.uri, &status.branch, default_branch
+            );
+            run_steps(
+                follow_default_branch_steps(&status.dir, default_branch),
+                timeout,
+                retries,
+            )?
+        }
+        _ => return Ok(false),
+    };
+    if !output.status.success() {
+        warn!(
+            "{}: update is failure [Exit code: {}] {}",
+            &status.uri,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.status.success())
+}
+
+// Steps run in order until one fails. Only those that reach the remote are retried,
+// since a local step that already succeeded would fail when run again.
+struct Step {
+    cmd: Command,
+    network: bool,
+}
+
+fn run_steps(steps: Vec<Step>, timeout: Duration, retries: u32) -> Result<Output> {
+    let mut last = None;
+    for mut step in steps {
+        let output = if step.network {
+            network::run(&mut step.cmd, timeout, retries)?
+        } else {
+            interrupt::output_with_timeout(&mut step.cmd, timeout)?
+        };
+        if !output.status.success() {
+            return Ok(output);
         }
+        last = Some(output);
     }
+    last.ok_or_else(|| anyhow!("no update steps"))
+}
+
+fn git_step(dir: &str, args: &[&str], network: bool) -> Step {
+    let mut cmd = network::command("git");
+    cmd.args(args).current_dir(dir);
+    Step { cmd, network }
+}
+
+fn get_head_rev(dir: &str) -> String {
+    git2::Repository::open(dir)
+        .and_then(|r| r.revparse_single("HEAD").map(|o| o.id().to_string()))
+        .unwrap_or_default()
+}
+
+fn update_repository_by_command(dir: &str) -> Command {
+    let mut cmd = network::command("git");
+    cmd.args(["pull", "--no-stat", "--recurse-submodules"])
+        .current_dir(dir);
+    cmd
+}
 
-    for mut c in children {
-        c.wait()?;
+fn checkout_tag_steps(dir: &str, tag: &str) -> Vec<Step> {
+    vec![
+        git_step(
+            dir,
+            &[
+                "checkout",
+                "--quiet",
+                "--detach",
+                &format!("refs/tags/{}", tag),
+            ],
+            false,
+        ),
+        git_step(dir, &["submodule", "update", "--init", "--recursive"], true),
+    ]
+}
+
+// Switches to the default branch, creating it from origin when missing; the branch
+// that was checked out before is left as it is
+fn follow_default_branch_steps(dir: &str, default_branch: &str) -> Vec<Step> {
+    let upstream = format!("origin/{}", default_branch);
+    vec![
+        git_step(dir, &["remote", "set-head", "origin", "--auto"], true),
+        git_step(dir, &["checkout", "--quiet", default_branch], false),
+        git_step(
+            dir,
+            &[
+                "branch",
+                "--quiet",
+                &format!("--set-upstream-to={}", upstream),
+                default_branch,
+            ],
+            false,
+        ),
+        git_step(dir, &["merge", "--ff-only", &upstream], false),
+        git_step(dir, &["submodule", "update", "--init", "--recursive"], true),
+    ]
+}
+
+fn update_submodules_by_command(
+    status: &GitStatus,
+    timeout: Duration,
+    retries: u32,
+) -> Result<bool> {
+    let output = network::run(
+        network::command("git")
+            .args(["submodule", "update", "--init", "--recursive"])
+            .current_dir(&status.dir),
+        timeout,
+        retries,
+    )?;
+    if !output.status.success() {
+        warn!(
+            "git submodule update is failure [Exit code: {}]",
+            output.status
+        );
     }
-    Ok(())
+    Ok(output.status.success())
 }
 
-fn update_repository_by_command<S: Into<String>>(dir: S) -> Result<Child> {
-    let child = std::process::Command::new("git")
-        .args(&["pull", "--no-stat", "--recurse-submodules"])
-        .current_dir(dir.into())
-        .stdout(std::process::Stdio::null())
-        .spawn()?;
-    Ok(child)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{git, temp_dir};
+
+    #[test]
+    fn follow_default_branch_keeps_local_branches() -> Result<()> {
+        let root = temp_dir("dotplugs_update");
+        let upstream = format!("{}/upstream", root);
+        let plugin = format!("{}/plugin", root);
+        std::fs::create_dir_all(&upstream)?;
+        git(&["init"], &upstream)?;
+        git(&["commit", "--allow-empty", "-m", "first"], &upstream)?;
+        git(&["clone", "upstream", "plugin"], &root)?;
+        git(&["checkout", "-b", "feature"], &plugin)?;
+        git(&["commit", "--allow-empty", "-m", "local"], &plugin)?;
+        git(&["branch", "main", "master"], &plugin)?;
+
+        git(&["branch", "-m", "master", "main"], &upstream)?;
+        git(&["commit", "--allow-empty", "-m", "second"], &upstream)?;
+        git(&["fetch", "-p"], &plugin)?;
+        let output = run_steps(
+            follow_default_branch_steps(&plugin, "main"),
+            Duration::from_secs(10),
+            0,
+        )?;
+        assert!(output.status.success());
+
+        let repo = git2::Repository::open(&plugin)?;
+        assert_eq!(crate::git::branch::get_current_branch(&repo)?, "main");
+        assert_eq!(
+            repo.revparse_single("main")?.id(),
+            repo.revparse_single("origin/main")?.id()
+        );
+        assert!(repo.find_branch("feature", git2::BranchType::Local).is_ok());
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
 }
@@ -0,0 +1,82 @@
+use crate::interrupt::TimedOut;
+use anyhow::Result;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct HookOutput {
+    pub command: String,
+    pub output: String,
+    pub success: bool,
+    pub timed_out: bool,
+}
+
+pub fn run<P: AsRef<Path>>(
+    command: &str,
+    dir: P,
+    envs: &[(&str, &str)],
+    timeout: Duration,
+) -> Result<HookOutput> {
+    log::debug!("hook: {} in {:?}", command, dir.as_ref());
+    let result = crate::interrupt::output_with_timeout(
+        Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .current_dir(dir)
+            .envs(envs.iter().cloned()),
+        timeout,
+    );
+    let output = match result {
+        Ok(o) => o,
+        Err(e) if e.is::<TimedOut>() => {
+            return Ok(HookOutput {
+                command: command.to_string(),
+                output: e.to_string(),
+                success: false,
+                timed_out: true,
+            })
+        }
+        Err(e) => return Err(e),
+    };
+    let mut s = String::from_utf8_lossy(&output.stdout).to_string();
+    s.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok(HookOutput {
+        command: command.to_string(),
+        output: s.trim_end().to_string(),
+        success: output.status.success(),
+        timed_out: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_with_env_ok() -> Result<()> {
+        let out = run(
+            "echo $DOTPLUGS_NEW_REV; pwd",
+            "/tmp",
+            &[("DOTPLUGS_NEW_REV", "abc123")],
+            Duration::from_secs(10),
+        )?;
+        assert!(out.success);
+        assert!(!out.timed_out);
+        assert!(out.output.starts_with("abc123\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn run_timed_out() -> Result<()> {
+        let out = run(
+            "sleep 5; echo done",
+            "/tmp",
+            &[],
+            Duration::from_millis(200),
+        )?;
+        assert!(!out.success);
+        assert!(out.timed_out);
+        Ok(())
+    }
+}
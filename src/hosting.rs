@@ -0,0 +1,477 @@
+pub mod attention;
+pub mod cache;
+pub mod gitea;
+pub mod github;
+pub mod gitlab;
+pub mod history;
+pub mod releases;
+pub mod sourcehut;
+
+use crate::repository::{RemoteUrl, Repository};
+use anyhow::Result;
+use cache::{Cache, Entry};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use log::*;
+use releases::ReleaseNote;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// Batches per host sent together before its rate limit is looked at again
+const WAVE_SIZE: usize = 4;
+
+#[derive(Debug, Default, Serialize)]
+pub struct Lookup {
+    pub repositories: Vec<RepoInfo>,
+    pub not_found: Vec<String>,
+    // hosts skipped because they need a token we could not find
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unavailable: Vec<String>,
+    #[serde(skip)]
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl Lookup {
+    pub fn get(&self, remote: &RemoteUrl) -> Option<&RepoInfo> {
+        let key = remote.to_string();
+        self.repositories
+            .iter()
+            .find(|i| i.requested.eq_ignore_ascii_case(&key))
+    }
+
+    pub fn is_not_found(&self, remote: &RemoteUrl) -> bool {
+        let key = remote.to_string();
+        self.not_found.iter().any(|n| n.eq_ignore_ascii_case(&key))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoInfo {
+    pub requested: String,
+    pub name_with_owner: String,
+    pub description: Option<String>,
+    pub is_archived: bool,
+    pub is_fork: bool,
+    pub license: Option<String>,
+    pub primary_language: Option<String>,
+    pub stargazers: i64,
+    pub open_issues: i64,
+    pub updated_at: NaiveDateTime,
+    pub pushed_at: Option<NaiveDateTime>,
+    pub latest_release: Option<Release>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub published_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    pub limit: i64,
+    pub cost: i64,
+    pub remaining: i64,
+    pub reset_at: DateTime<Utc>,
+}
+
+impl RateLimit {
+    // Leaves some points for other tools sharing the token
+    pub fn is_low(&self, now: DateTime<Utc>) -> bool {
+        self.remaining < (self.limit / 20).max(self.cost) && self.reset_at > now
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProviderKind {
+    #[serde(rename = "github")]
+    GitHub,
+    #[serde(rename = "gitlab")]
+    GitLab,
+    #[serde(alias = "forgejo")]
+    Gitea,
+    Sourcehut,
+}
+
+pub trait HostingProvider: Send + Sync {
+    // false when the provider needs a token and none was found
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn batch_size(&self) -> usize {
+        10
+    }
+
+    fn fetch(&self, remotes: &[RemoteUrl]) -> Result<Lookup>;
+
+    // Newest first; only GitHub serves release bodies for now
+    fn release_notes(&self, _remote: &RemoteUrl) -> Result<Vec<ReleaseNote>> {
+        Ok(vec![])
+    }
+}
+
+pub fn provider(host: &str) -> Result<Option<Box<dyn HostingProvider>>> {
+    let config = crate::config::get()?;
+    let hosting = config.hosting.hosts.get(host);
+    let kind = match hosting.and_then(|h| h.kind).or_else(|| default_kind(host)) {
+        Some(k) => k,
+        None => return Ok(None),
+    };
+    let base_url = hosting
+        .and_then(|h| h.base_url.clone())
+        .unwrap_or_else(|| default_base_url(kind, host));
+    let token = hosting.and_then(|h| h.token.clone());
+    let provider: Box<dyn HostingProvider> = match kind {
+        ProviderKind::GitHub => Box::new(github::GitHub {
+            base_url,
+            token: token.or_else(|| github::token::get(host)),
+        }),
+        ProviderKind::GitLab => Box::new(gitlab::GitLab {
+            base_url,
+            token: token.or_else(|| env_token("GITLAB_TOKEN")),
+        }),
+        ProviderKind::Gitea => Box::new(gitea::Gitea {
+            base_url,
+            token: token.or_else(|| env_token("GITEA_TOKEN")),
+        }),
+        ProviderKind::Sourcehut => Box::new(sourcehut::Sourcehut {
+            base_url,
+            token: token.or_else(|| env_token("SRHT_TOKEN")),
+        }),
+    };
+    Ok(Some(provider))
+}
+
+fn default_kind(host: &str) -> Option<ProviderKind> {
+    match host {
+        "github.com" => Some(ProviderKind::GitHub),
+        "gitlab.com" => Some(ProviderKind::GitLab),
+        "codeberg.org" => Some(ProviderKind::Gitea),
+        "git.sr.ht" => Some(ProviderKind::Sourcehut),
+        _ if github::token::is_gh_host(host) => Some(ProviderKind::GitHub),
+        _ => None,
+    }
+}
+
+fn default_base_url(kind: ProviderKind, host: &str) -> String {
+    match kind {
+        ProviderKind::GitHub if host == "github.com" => {
+            "https://api.github.com/graphql".to_string()
+        }
+        ProviderKind::GitHub => format!("https://{}/api/graphql", host),
+        ProviderKind::GitLab => format!("https://{}/api/v4", host),
+        ProviderKind::Gitea => format!("https://{}/api/v1", host),
+        ProviderKind::Sourcehut => format!("https://{}/query", host),
+    }
+}
+
+fn env_token(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|t| !t.is_empty())
+}
+
+// Serves fresh entries from the cache and only asks the hosting services for the rest
+pub fn create_info(repos: &[Repository], refresh: bool) -> Result<Lookup> {
+    let config = crate::config::get()?;
+    let remotes = convert_remotes(repos);
+    let path = cache::path()?;
+    let mut cache = Cache::load(&path).unwrap_or_else(|e| {
+        warn!("ignoring broken cache {:?}: {}", &path, e);
+        Cache::default()
+    });
+    let now = Utc::now().naive_utc();
+    let expired: Vec<&RemoteUrl> = remotes
+        .iter()
+        .filter(|r| refresh || !cache.is_fresh(&r.to_string(), config.cache_ttl(), now))
+        .collect();
+
+    let mut hosts: Vec<&str> = expired.iter().filter_map(|r| r.host.as_deref()).collect();
+    hosts.sort_unstable();
+    hosts.dedup();
+    let mut unavailable = vec![];
+    let mut jobs: Vec<(String, Arc<dyn HostingProvider>, Vec<RemoteUrl>)> = vec![];
+    for host in hosts {
+        let provider = match provider(host)? {
+            Some(p) => p,
+            None => continue,
+        };
+        if !provider.is_available() {
+            debug!("skipping {}, no token", host);
+            unavailable.push(host.to_string());
+            continue;
+        }
+        if let Some(limit) = cache.rate_limits.get(host).filter(|l| l.is_low(Utc::now())) {
+            warn_rate_limit(host, limit);
+            continue;
+        }
+        let targets: Vec<RemoteUrl> = expired
+            .iter()
+            .filter(|r| r.host.as_deref() == Some(host))
+            .map(|r| (*r).clone())
+            .collect();
+        jobs.push((host.to_string(), Arc::from(provider), targets));
+    }
+    for (host, result) in fetch_batches(jobs)? {
+        store(&host, result, &mut cache, now);
+    }
+    if !expired.is_empty() {
+        if let Err(e) = cache.save(&path) {
+            warn!("could not write cache {:?}: {}", &path, e);
+        }
+    }
+
+    let mut lookup = Lookup {
+        unavailable,
+        ..Default::default()
+    };
+    for r in &remotes {
+        match cache.get(&r.to_string()) {
+            Some(Entry {
+                info: Some(info), ..
+            }) => lookup.repositories.push(info.clone()),
+            Some(Entry { info: None, .. }) => lookup.not_found.push(r.to_string()),
+            None => debug!("no metadata for {}", r),
+        }
+    }
+    debug!("{:?}", lookup);
+    Ok(lookup)
+}
+
+// All hosts' batches go out on the pool in waves of up to WAVE_SIZE per host. A host whose
+// rate limit turns out low gets no further waves and its remaining entries stay stale.
+fn fetch_batches(
+    jobs: Vec<(String, Arc<dyn HostingProvider>, Vec<RemoteUrl>)>,
+) -> Result<Vec<(String, Result<Lookup>)>> {
+    let mut pending: Vec<_> = jobs
+        .into_iter()
+        .map(|(host, provider, targets)| {
+            let chunks: Vec<Vec<RemoteUrl>> = targets
+                .chunks(provider.batch_size())
+                .map(|c| c.to_vec())
+                .collect();
+            (host, provider, chunks)
+        })
+        .collect();
+    let mut results = vec![];
+    loop {
+        let mut wave = vec![];
+        for (host, provider, chunks) in pending.iter_mut() {
+            let n = WAVE_SIZE.min(chunks.len());
+            wave.extend(
+                chunks
+                    .drain(..n)
+                    .map(|c| (host.clone(), Arc::clone(provider), c)),
+            );
+        }
+        if wave.is_empty() || crate::interrupt::is_interrupted() {
+            break;
+        }
+        let done = crate::git::map_in_pool(wave, |(host, provider, chunk)| {
+            let result = provider.fetch(&chunk);
+            (host, result)
+        })?;
+        for (host, result) in done.into_iter().flatten() {
+            let low = result
+                .as_ref()
+                .ok()
+                .and_then(|l| l.rate_limit.as_ref())
+                .filter(|l| l.is_low(Utc::now()));
+            if let Some(limit) = low {
+                if let Some((_, _, chunks)) = pending
+                    .iter_mut()
+                    .find(|(h, _, c)| *h == host && !c.is_empty())
+                {
+                    warn_rate_limit(&host, limit);
+                    chunks.clear();
+                }
+            }
+            results.push((host, result));
+        }
+    }
+    Ok(results)
+}
+
+fn warn_rate_limit(host: &str, limit: &RateLimit) {
+    warn!(
+        "{} rate limit is low ({} left until {}), using cached data",
+        host, limit.remaining, limit.reset_at
+    );
+}
+
+// A failing batch keeps its stale entries so the other hosts still show up
+fn store(host: &str, result: Result<Lookup>, cache: &mut Cache, now: NaiveDateTime) {
+    let l = match result {
+        Ok(l) => l,
+        Err(e) => {
+            warn!("could not fetch metadata from {}: {}", host, e);
+            return;
+        }
+    };
+    if let Some(limit) = l.rate_limit {
+        debug!("{:?}", limit);
+        let newer = cache
+            .rate_limits
+            .get(host)
+            .is_none_or(|old| old.reset_at != limit.reset_at || old.remaining > limit.remaining);
+        if newer {
+            cache.rate_limits.insert(host.to_string(), limit);
+        }
+    }
+    let readings: Vec<history::Reading> = l
+        .repositories
+        .iter()
+        .map(|i| history::Reading::new(host, i, now))
+        .collect();
+    if let Err(e) = history::path().and_then(|p| history::append(&p, &readings)) {
+        warn!("could not record history: {}", e);
+    }
+    for info in l.repositories {
+        let key = info.requested.to_string();
+        cache.insert(&key, Some(info), now);
+    }
+    for key in &l.not_found {
+        cache.insert(key, None, now);
+    }
+}
+
+fn convert_remotes(repos: &[Repository]) -> Vec<RemoteUrl> {
+    let mut remotes: Vec<RemoteUrl> = vec![];
+    for r in repos.iter().filter_map(|r| r.remote_url().ok()) {
+        let duplicated = remotes
+            .iter()
+            .any(|n| n.to_string().eq_ignore_ascii_case(&r.to_string()));
+        if r.host.is_some() && !duplicated {
+            remotes.push(r);
+        }
+    }
+    remotes.sort_by_key(|r| r.host.clone());
+    remotes
+}
+
+pub fn parse_time(s: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(s).ok().map(|d| d.naive_utc())
+}
+
+// Percent-encodes everything but unreserved characters, as GitLab wants for project paths
+pub fn encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod mock {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    // Answers each request whose path (without query) equals a route; anything else is a 404
+    pub fn serve(routes: Vec<(&'static str, u16, String)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(s) => s,
+                    Err(_) => continue,
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap_or(0);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                    let lower = line.to_lowercase();
+                    if let Some(v) = lower.strip_prefix("content-length:") {
+                        content_length = v.trim().parse().unwrap_or(0);
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap_or(());
+
+                let path = request_line.split(' ').nth(1).unwrap_or("/");
+                let path = path.split('?').next().unwrap_or(path);
+                let (status, body) = routes
+                    .iter()
+                    .find(|(p, _, _)| *p == path)
+                    .map(|(_, s, b)| (*s, b.as_str()))
+                    .unwrap_or((404, r#"{"message":"404 Not Found"}"#));
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap_or(());
+            }
+        });
+        format!("http://{}", addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Limited {
+        remaining: i64,
+        calls: std::sync::Mutex<usize>,
+    }
+
+    impl HostingProvider for Limited {
+        fn batch_size(&self) -> usize {
+            2
+        }
+
+        fn fetch(&self, _remotes: &[RemoteUrl]) -> Result<Lookup> {
+            *self.calls.lock().unwrap() += 1;
+            Ok(Lookup {
+                rate_limit: Some(RateLimit {
+                    limit: 5000,
+                    cost: 1,
+                    remaining: self.remaining,
+                    reset_at: Utc::now() + chrono::Duration::hours(1),
+                }),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn fetch_batches_stops_on_low_rate_limit() -> Result<()> {
+        let targets = (0..20)
+            .map(|i| RemoteUrl::parse(&format!("https://github.com/owner/plugin{}", i)))
+            .collect::<Result<Vec<_>>>()?;
+        let low = Arc::new(Limited {
+            remaining: 10,
+            calls: std::sync::Mutex::new(0),
+        });
+        let plenty = Arc::new(Limited {
+            remaining: 5000,
+            calls: std::sync::Mutex::new(0),
+        });
+        let results = fetch_batches(vec![
+            ("github.com".to_string(), low.clone(), targets.clone()),
+            ("gitlab.com".to_string(), plenty.clone(), targets),
+        ])?;
+        // The first wave of the low host still goes out, the rest of its batches do not
+        assert_eq!(*low.calls.lock().unwrap(), WAVE_SIZE);
+        assert_eq!(*plenty.calls.lock().unwrap(), 10);
+        assert_eq!(results.len(), WAVE_SIZE + 10);
+        Ok(())
+    }
+
+    #[test]
+    fn encode_ok() {
+        assert_eq!(encode("group/sub/repo.vim"), "group%2Fsub%2Frepo.vim");
+        assert_eq!(encode("~user"), "~user");
+    }
+}
@@ -0,0 +1,165 @@
+use super::Lookup;
+use crate::git::GitStatus;
+use crate::repository::RemoteUrl;
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Attention {
+    NotFound,
+    Archived,
+    Stale {
+        pushed_at: NaiveDateTime,
+    },
+    Moved {
+        name_with_owner: String,
+        suggested_uri: String,
+    },
+}
+
+pub fn annotate(
+    statuses: &mut [GitStatus],
+    lookup: &Lookup,
+    stale_after: Duration,
+    now: NaiveDateTime,
+) {
+    for s in statuses.iter_mut() {
+        let remote = match RemoteUrl::parse(&s.uri) {
+            Ok(r) => r,
+            _ => continue,
+        };
+        s.attention.clear();
+        if lookup.is_not_found(&remote) {
+            s.attention.push(Attention::NotFound);
+            continue;
+        }
+        let info = match lookup.get(&remote) {
+            Some(info) => info,
+            None => continue,
+        };
+
+        if info.is_archived {
+            s.attention.push(Attention::Archived);
+        }
+        if let Some(pushed_at) = info.pushed_at {
+            if now - pushed_at > stale_after {
+                s.attention.push(Attention::Stale { pushed_at });
+            }
+        }
+        if let Some(moved) = moved(lookup, &remote) {
+            s.attention.push(moved);
+        }
+    }
+}
+
+// Renamed and transferred repositories are answered under their new name
+pub fn moved(lookup: &Lookup, remote: &RemoteUrl) -> Option<Attention> {
+    let info = lookup.get(remote)?;
+    if info
+        .name_with_owner
+        .eq_ignore_ascii_case(&remote.name_with_owner())
+    {
+        return None;
+    }
+    Some(Attention::Moved {
+        name_with_owner: info.name_with_owner.to_string(),
+        suggested_uri: remote.with_name_with_owner(&info.name_with_owner).url(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::UpdateStatus;
+    use crate::hosting::RepoInfo;
+
+    fn info(
+        requested: &str,
+        name_with_owner: &str,
+        is_archived: bool,
+        pushed_at: &str,
+    ) -> RepoInfo {
+        RepoInfo {
+            requested: requested.to_string(),
+            name_with_owner: name_with_owner.to_string(),
+            description: None,
+            is_archived,
+            is_fork: false,
+            license: None,
+            primary_language: None,
+            stargazers: 0,
+            open_issues: 0,
+            updated_at: NaiveDateTime::parse_from_str(pushed_at, "%Y-%m-%d %H:%M:%S").unwrap(),
+            pushed_at: NaiveDateTime::parse_from_str(pushed_at, "%Y-%m-%d %H:%M:%S").ok(),
+            latest_release: None,
+        }
+    }
+
+    fn status(uri: &str) -> GitStatus {
+        GitStatus {
+            uri: uri.to_string(),
+            dir: "/home/test/.vim/plugged/x".to_string(),
+            branch: "master".to_string(),
+            status: UpdateStatus::Already,
+            submodules: vec![],
+            track: Default::default(),
+            current_tag: None,
+            latest_tag: None,
+            default_branch: None,
+            manager: None,
+            attention: vec![],
+        }
+    }
+
+    #[test]
+    fn annotate_ok() {
+        let mut statuses = vec![
+            status("https://github.com/tpope/vim-fugitive.git"),
+            status("https://github.com/kana/vim-operator-user.git"),
+            status("git@github.com:old-owner/coc.nvim.git"),
+            status("https://gitlab.com/someone/plugin.git"),
+            status("https://github.com/gone/plugin.git"),
+        ];
+        let lookup = Lookup {
+            repositories: vec![
+                info(
+                    "tpope/vim-fugitive",
+                    "tpope/vim-fugitive",
+                    false,
+                    "2020-05-01 00:00:00",
+                ),
+                info(
+                    "kana/vim-operator-user",
+                    "kana/vim-operator-user",
+                    true,
+                    "2015-01-01 00:00:00",
+                ),
+                info(
+                    "old-owner/coc.nvim",
+                    "neoclide/coc.nvim",
+                    false,
+                    "2020-05-01 00:00:00",
+                ),
+            ],
+            not_found: vec!["gone/plugin".to_string()],
+            ..Default::default()
+        };
+        let now =
+            NaiveDateTime::parse_from_str("2020-06-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        annotate(&mut statuses, &lookup, Duration::days(730), now);
+
+        assert!(statuses[0].attention.is_empty());
+        assert_eq!(statuses[1].attention.len(), 2);
+        assert_eq!(statuses[1].attention[0], Attention::Archived);
+        assert_eq!(
+            statuses[2].attention,
+            vec![Attention::Moved {
+                name_with_owner: "neoclide/coc.nvim".to_string(),
+                suggested_uri: "git@github.com:neoclide/coc.nvim.git".to_string(),
+            }]
+        );
+        assert!(statuses[3].attention.is_empty());
+        assert_eq!(statuses[4].attention, vec![Attention::NotFound]);
+    }
+}
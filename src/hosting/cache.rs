@@ -0,0 +1,177 @@
+use super::{RateLimit, RepoInfo};
+use crate::repository::RemoteUrl;
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Cache {
+    #[serde(default)]
+    pub entries: HashMap<String, Entry>,
+    #[serde(default)]
+    pub rate_limits: HashMap<String, RateLimit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub fetched_at: NaiveDateTime,
+    // None when the hosting service reported the repository as not found
+    pub info: Option<RepoInfo>,
+}
+
+impl Cache {
+    pub fn load(path: &Path) -> Result<Cache> {
+        if !path.exists() {
+            return Ok(Cache::default());
+        }
+        let s = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&s)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.get(&key.to_lowercase())
+    }
+
+    pub fn is_fresh(&self, key: &str, ttl: Duration, now: NaiveDateTime) -> bool {
+        self.get(key).is_some_and(|e| now - e.fetched_at < ttl)
+    }
+
+    // Names the repository was looked up under before it moved to the remote's current name
+    pub fn previous_names(&self, remote: &RemoteUrl) -> Vec<String> {
+        let current = remote.to_string().to_lowercase();
+        let prefix = match (&remote.host, remote.is_github()) {
+            (Some(host), false) => format!("{}/", host),
+            _ => "".to_string(),
+        };
+        let mut names: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(key, e)| {
+                **key != current
+                    && e.info.as_ref().is_some_and(|i| {
+                        i.name_with_owner
+                            .eq_ignore_ascii_case(&remote.name_with_owner())
+                    })
+            })
+            .filter_map(|(key, _)| key.strip_prefix(&prefix))
+            // github.com keys carry no host, so anything longer belongs to another host
+            .filter(|name| !prefix.is_empty() || name.matches('/').count() == 1)
+            .map(|name| name.to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn insert(&mut self, key: &str, info: Option<RepoInfo>, now: NaiveDateTime) {
+        self.entries.insert(
+            key.to_lowercase(),
+            Entry {
+                fetched_at: now,
+                info,
+            },
+        );
+    }
+}
+
+pub fn path() -> Result<PathBuf> {
+    let mut path = crate::config::cache_dir()?;
+    path.push("github.json");
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn save_and_load_ok() -> Result<()> {
+        let path = PathBuf::from(format!("{}/github.json", temp_dir("dotplugs_cache")));
+        let now = NaiveDateTime::parse_from_str("2020-06-01 00:00:00", "%Y-%m-%d %H:%M:%S")?;
+
+        let mut cache = Cache::load(&path)?;
+        assert!(cache.entries.is_empty());
+        cache.insert("Gone/Plugin", None, now - Duration::days(2));
+        cache.insert(
+            "tpope/vim-fugitive",
+            Some(RepoInfo {
+                requested: "tpope/vim-fugitive".to_string(),
+                name_with_owner: "tpope/vim-fugitive".to_string(),
+                description: None,
+                is_archived: false,
+                is_fork: false,
+                license: Some("Vim".to_string()),
+                primary_language: None,
+                stargazers: 1,
+                open_issues: 2,
+                updated_at: now,
+                pushed_at: Some(now),
+                latest_release: None,
+            }),
+            now,
+        );
+        cache.save(&path)?;
+
+        let cache = Cache::load(&path)?;
+        assert!(cache.get("gone/plugin").unwrap().info.is_none());
+        assert!(!cache.is_fresh("gone/plugin", Duration::days(1), now));
+        assert!(cache.is_fresh("TPope/vim-fugitive", Duration::days(1), now));
+        assert_eq!(
+            cache
+                .get("tpope/vim-fugitive")
+                .unwrap()
+                .info
+                .as_ref()
+                .unwrap()
+                .license,
+            Some("Vim".to_string())
+        );
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap_or(());
+        Ok(())
+    }
+
+    #[test]
+    fn previous_names_ok() -> Result<()> {
+        let now = NaiveDateTime::parse_from_str("2020-06-01 00:00:00", "%Y-%m-%d %H:%M:%S")?;
+        let info = |nwo: &str| RepoInfo {
+            requested: nwo.to_string(),
+            name_with_owner: nwo.to_string(),
+            description: None,
+            is_archived: false,
+            is_fork: false,
+            license: None,
+            primary_language: None,
+            stargazers: 0,
+            open_issues: 0,
+            updated_at: now,
+            pushed_at: None,
+            latest_release: None,
+        };
+        let mut cache = Cache::default();
+        cache.insert("Owner/old", Some(info("NewOwner/new")), now);
+        cache.insert("newowner/new", Some(info("NewOwner/new")), now);
+        cache.insert(
+            "git.example.com/owner/gone",
+            Some(info("NewOwner/new")),
+            now,
+        );
+
+        let remote = RemoteUrl::parse("git@github.com:newowner/new.git")?;
+        assert_eq!(cache.previous_names(&remote), vec!["owner/old"]);
+        let remote = RemoteUrl::parse("https://git.example.com/newowner/new")?;
+        assert_eq!(cache.previous_names(&remote), vec!["owner/gone"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use super::{parse_time, HostingProvider, Lookup, Release, RepoInfo};
+use crate::repository::RemoteUrl;
+use anyhow::Result;
+use log::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// Forgejo (Codeberg) speaks the same API
+pub struct Gitea {
+    pub base_url: String,
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Repo {
+    full_name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    fork: bool,
+    #[serde(default)]
+    licenses: Vec<String>,
+    stars_count: i64,
+    #[serde(default)]
+    open_issues_count: i64,
+    updated_at: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct RepoRelease {
+    tag_name: String,
+    published_at: Option<String>,
+}
+
+impl HostingProvider for Gitea {
+    fn fetch(&self, remotes: &[RemoteUrl]) -> Result<Lookup> {
+        let mut lookup = Lookup::default();
+        for r in remotes {
+            match self.get_repo(r)? {
+                Some(info) => lookup.repositories.push(info),
+                None => lookup.not_found.push(r.to_string()),
+            }
+        }
+        Ok(lookup)
+    }
+}
+
+impl Gitea {
+    fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        let client = crate::http::client()?;
+        let mut req = client.get(&format!("{}{}", self.base_url, path));
+        if let Some(token) = &self.token {
+            req = req.header("Authorization", format!("token {}", token));
+        }
+        let res = req.send()?;
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body = res.error_for_status()?.json()?;
+        Ok(Some(body))
+    }
+
+    fn get_repo(&self, remote: &RemoteUrl) -> Result<Option<RepoInfo>> {
+        let path = format!("/repos/{}", remote.name_with_owner());
+        let repo: Repo = match self.get(&path)? {
+            Some(r) => r,
+            None => return Ok(None),
+        };
+        debug!("{:?}", repo);
+        let languages: HashMap<String, i64> = self
+            .get(&format!("{}/languages", path))?
+            .unwrap_or_default();
+        // 404 when the repository has no release
+        let release: Option<RepoRelease> = self.get(&format!("{}/releases/latest", path))?;
+        let updated_at = parse_time(&repo.updated_at);
+        Ok(Some(RepoInfo {
+            requested: remote.to_string(),
+            name_with_owner: repo.full_name,
+            description: Some(repo.description).filter(|d| !d.is_empty()),
+            is_archived: repo.archived,
+            is_fork: repo.fork,
+            license: repo.licenses.into_iter().next(),
+            primary_language: languages
+                .into_iter()
+                .max_by_key(|(_, bytes)| *bytes)
+                .map(|(l, _)| l),
+            stargazers: repo.stars_count,
+            open_issues: repo.open_issues_count,
+            updated_at: updated_at.unwrap_or_default(),
+            pushed_at: updated_at,
+            latest_release: release.map(|r| Release {
+                tag_name: r.tag_name,
+                published_at: r.published_at.as_deref().and_then(parse_time),
+            }),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetch_ok() -> Result<()> {
+        let repo = r#"{
+            "full_name": "owner/plugin.nvim",
+            "description": "A plugin",
+            "archived": false,
+            "fork": false,
+            "licenses": ["Apache-2.0"],
+            "stars_count": 12,
+            "open_issues_count": 1,
+            "updated_at": "2021-03-04T05:06:07+09:00"
+        }"#;
+        let base_url = crate::hosting::mock::serve(vec![
+            ("/api/v1/repos/owner/plugin.nvim", 200, repo.to_string()),
+            (
+                "/api/v1/repos/owner/plugin.nvim/languages",
+                200,
+                r#"{ "Lua": 5000, "Vim Script": 100 }"#.to_string(),
+            ),
+        ]);
+        let gitea = Gitea {
+            base_url: format!("{}/api/v1", base_url),
+            token: Some("token".to_string()),
+        };
+        let remotes = vec![
+            RemoteUrl::parse("https://codeberg.org/owner/plugin.nvim")?,
+            RemoteUrl::parse("https://codeberg.org/owner/gone")?,
+        ];
+        let lookup = gitea.fetch(&remotes)?;
+        let info = &lookup.repositories[0];
+        assert_eq!(info.requested, "codeberg.org/owner/plugin.nvim");
+        assert_eq!(info.license.as_deref(), Some("Apache-2.0"));
+        assert_eq!(info.primary_language.as_deref(), Some("Lua"));
+        assert_eq!(
+            info.pushed_at.unwrap().format("%Y-%m-%d %H:%M").to_string(),
+            "2021-03-03 20:06"
+        );
+        assert!(info.latest_release.is_none());
+        assert_eq!(
+            lookup.not_found,
+            vec!["codeberg.org/owner/gone".to_string()]
+        );
+        Ok(())
+    }
+}
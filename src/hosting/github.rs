@@ -0,0 +1,461 @@
+pub mod token;
+
+use super::releases::ReleaseNote;
+use super::{parse_time, HostingProvider, Lookup, RateLimit, Release, RepoInfo};
+use crate::repository::RemoteUrl;
+use anyhow::{anyhow, Result};
+use graphql_client::*;
+use log::*;
+use serde::*;
+use std::collections::HashMap;
+
+type DateTime = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/hosting/github/graphql/schema.graphql",
+    query_path = "src/hosting/github/graphql/release_notes_query.graphql",
+    response_derives = "Debug"
+)]
+struct ReleaseNotes;
+
+const BATCH_SIZE: usize = 50;
+// Only fragments; the batch query is built around them
+const FRAGMENTS: &str = include_str!("github/graphql/repo_batch_fragments.graphql");
+
+#[derive(Deserialize, Debug)]
+struct BatchResponse {
+    data: Option<BatchData>,
+    #[serde(default)]
+    errors: Vec<BatchError>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BatchData {
+    #[serde(rename = "rateLimit")]
+    rate_limit: Option<RateLimit>,
+    #[serde(flatten)]
+    repositories: HashMap<String, Option<RepoNode>>,
+}
+
+// The shape of the RepoInfo fragment
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RepoNode {
+    name_with_owner: String,
+    description: Option<String>,
+    is_archived: bool,
+    is_fork: bool,
+    license_info: Option<LicenseNode>,
+    primary_language: Option<NameNode>,
+    stargazers: CountNode,
+    issues: CountNode,
+    releases: ReleasesNode,
+    updated_at: DateTime,
+    pushed_at: Option<DateTime>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LicenseNode {
+    spdx_id: Option<String>,
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct NameNode {
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CountNode {
+    total_count: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReleasesNode {
+    nodes: Option<Vec<Option<ReleaseNode>>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ReleaseNode {
+    tag: Option<NameNode>,
+    published_at: Option<DateTime>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BatchError {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    message: String,
+}
+
+pub struct GitHub {
+    pub base_url: String,
+    pub token: Option<String>,
+}
+
+impl HostingProvider for GitHub {
+    fn is_available(&self) -> bool {
+        self.token.is_some()
+    }
+
+    fn batch_size(&self) -> usize {
+        BATCH_SIZE
+    }
+
+    fn fetch(&self, remotes: &[RemoteUrl]) -> Result<Lookup> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or(anyhow!("GitHub token not found"))?;
+        let response = download_repository_info(&self.base_url, remotes, token)?;
+        convert_github_info(remotes, response)
+    }
+
+    fn release_notes(&self, remote: &RemoteUrl) -> Result<Vec<ReleaseNote>> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or(anyhow!("GitHub token not found"))?;
+        let body = ReleaseNotes::build_query(release_notes::Variables {
+            owner: remote.owner().to_string(),
+            name: remote.name.to_string(),
+            count: super::releases::RELEASE_COUNT as i64,
+        });
+        let client = crate::http::client()?;
+        let response: Response<release_notes::ResponseData> = client
+            .post(&self.base_url)
+            .bearer_auth(token)
+            .json(&body)
+            .send()?
+            .error_for_status()?
+            .json()?;
+        debug!("{:?}", response);
+        convert_release_notes(response)
+    }
+}
+
+fn convert_release_notes(
+    response: Response<release_notes::ResponseData>,
+) -> Result<Vec<ReleaseNote>> {
+    if let Some(e) = response.errors.as_ref().and_then(|e| e.first()) {
+        return Err(anyhow!("{}", e.message));
+    }
+    let nodes = response
+        .data
+        .and_then(|d| d.repository)
+        .and_then(|r| r.releases.nodes)
+        .unwrap_or_default();
+    Ok(nodes
+        .into_iter()
+        .flatten()
+        .filter(|n| !n.is_draft)
+        .filter_map(|n| {
+            Some(ReleaseNote {
+                tag_name: n.tag?.name,
+                name: n.name.filter(|s| !s.is_empty()),
+                published_at: n.published_at.as_deref().and_then(parse_time),
+                body: n.description.unwrap_or_default(),
+            })
+        })
+        .collect())
+}
+
+// One aliased repository() field per plugin, all sharing the RepoInfo fragment, plus the rate limit
+fn build_query(names: &[RemoteUrl]) -> (String, serde_json::Value) {
+    let mut params = vec![];
+    let mut fields = vec![];
+    let mut variables = serde_json::Map::new();
+    for (i, n) in names.iter().enumerate() {
+        params.push(format!("$o{0}: String!, $n{0}: String!", i));
+        fields.push(format!(
+            "  r{0}: repository(owner: $o{0}, name: $n{0}) {{\n    ...RepoInfo\n  }}",
+            i
+        ));
+        variables.insert(format!("o{}", i), n.owner().into());
+        variables.insert(format!("n{}", i), n.name.as_str().into());
+    }
+    let query = format!(
+        "query RepoBatch({}) {{\n  ...RateLimitInfo\n{}\n}}\n\n{}",
+        params.join(", "),
+        fields.join("\n"),
+        FRAGMENTS
+    );
+    (query, variables.into())
+}
+
+fn download_repository_info(url: &str, names: &[RemoteUrl], token: &str) -> Result<BatchResponse> {
+    let (query, variables) = build_query(names);
+    let body = serde_json::json!({ "query": query, "variables": variables });
+
+    let client = crate::http::client()?;
+
+    let mut res = client
+        .post(url)
+        .bearer_auth(token)
+        .json(&body)
+        .send()?
+        .error_for_status()?;
+
+    let response_body: BatchResponse = res.json()?;
+    debug!("{:?}", response_body);
+    Ok(response_body)
+}
+
+// A missing repository comes back as null plus a NOT_FOUND error; anything else is fatal
+fn convert_github_info(names: &[RemoteUrl], response: BatchResponse) -> Result<Lookup> {
+    let errors: Vec<&BatchError> = response
+        .errors
+        .iter()
+        .filter(|e| e.kind.as_deref() != Some("NOT_FOUND"))
+        .collect();
+    if !errors.is_empty() {
+        error!("there are errors:");
+        for error in &errors {
+            error!("{}", error.message);
+        }
+        return Err(anyhow!("GraphQL error"));
+    }
+
+    let (rate_limit, mut data) = match response.data {
+        Some(d) => (d.rate_limit, d.repositories),
+        None => (None, HashMap::new()),
+    };
+    let mut lookup = Lookup {
+        rate_limit,
+        ..Default::default()
+    };
+    for (i, n) in names.iter().enumerate() {
+        match data.remove(&format!("r{}", i)).flatten() {
+            Some(r) => lookup.repositories.push(RepoInfo {
+                requested: n.to_string(),
+                name_with_owner: r.name_with_owner.to_string(),
+                description: r.description.clone(),
+                is_archived: r.is_archived,
+                is_fork: r.is_fork,
+                license: r
+                    .license_info
+                    .as_ref()
+                    .map(|l| l.spdx_id.clone().unwrap_or_else(|| l.name.clone())),
+                primary_language: r.primary_language.as_ref().map(|l| l.name.clone()),
+                stargazers: r.stargazers.total_count,
+                open_issues: r.issues.total_count,
+                updated_at: parse_time(&r.updated_at).unwrap_or_default(),
+                pushed_at: r.pushed_at.as_ref().and_then(|d| parse_time(d)),
+                latest_release: r.releases.nodes.iter().flatten().flatten().find_map(|n| {
+                    n.tag.as_ref().map(|t| Release {
+                        tag_name: t.name.to_string(),
+                        published_at: n.published_at.as_ref().and_then(|d| parse_time(d)),
+                    })
+                }),
+            }),
+            None => lookup.not_found.push(n.to_string()),
+        }
+    }
+    Ok(lookup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::Repository;
+    fn init() {
+        let _ = pretty_env_logger::formatted_builder()
+            .is_test(true)
+            .parse_filters("DEBUG")
+            .try_init();
+    }
+
+    #[test]
+    #[ignore]
+    fn create_info_works() -> Result<()> {
+        init();
+
+        let v = vec![
+            Repository {
+                uri: "https://git::@github.com/kana/vim-operator-user.git".to_string(),
+                dir: "/home/test/.vim/plugged/vim-operator-user/".to_string(),
+                ..Default::default()
+            },
+            Repository {
+                uri: "https://git::@github.com/moll/vim-bbye.git".to_string(),
+                dir: "/home/test/.vim/plugged/vim-bbye/".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let _ = crate::hosting::create_info(&v, true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn convert_github_info_ok() -> Result<()> {
+        init();
+
+        let data = r#"{
+            "data": {
+                "rateLimit": { "limit": 5000, "cost": 1, "remaining": 4999, "resetAt": "2020-06-01T01:00:00Z" },
+                "r0": {
+                    "nameWithOwner": "kana/vim-operator-user",
+                    "description": "Vim plugin: Define your own operator easily",
+                    "isArchived": true,
+                    "isFork": false,
+                    "licenseInfo": { "spdxId": "MIT", "name": "MIT License" },
+                    "primaryLanguage": { "name": "Vim script" },
+                    "stargazers": { "totalCount": 200 },
+                    "issues": { "totalCount": 3 },
+                    "releases": { "nodes": [{ "tag": { "name": "0.1.0" }, "publishedAt": "2014-01-02T03:04:05Z" }] },
+                    "updatedAt": "2020-01-01T00:00:00Z",
+                    "pushedAt": "2015-06-07T08:09:10Z"
+                },
+                "r1": {
+                    "nameWithOwner": "moll/vim-bbye",
+                    "description": null,
+                    "isArchived": false,
+                    "isFork": true,
+                    "licenseInfo": null,
+                    "primaryLanguage": null,
+                    "stargazers": { "totalCount": 500 },
+                    "issues": { "totalCount": 0 },
+                    "releases": { "nodes": [] },
+                    "updatedAt": "2020-01-01T00:00:00Z",
+                    "pushedAt": null
+                },
+                "r2": null
+            },
+            "errors": [{
+                "type": "NOT_FOUND",
+                "path": ["r2"],
+                "message": "Could not resolve to a Repository with the name 'gone/plugin'."
+            }]
+        }"#;
+        let names = vec![
+            RemoteUrl::parse("https://github.com/kana/vim-operator-user")?,
+            RemoteUrl::parse("https://github.com/Moll/vim-bbye")?,
+            RemoteUrl::parse("https://github.com/gone/plugin")?,
+        ];
+        let response: BatchResponse = serde_json::from_str(data)?;
+        let limit = response.data.as_ref().unwrap().rate_limit.clone().unwrap();
+        assert_eq!(limit.remaining, 4999);
+        assert!(!limit.is_low("2020-06-01T00:00:00Z".parse()?));
+        let lookup = convert_github_info(&names, response)?;
+        let info = &lookup.repositories;
+        assert_eq!(info.len(), 2);
+        assert!(info[0].is_archived);
+        assert_eq!(info[0].license.as_deref(), Some("MIT"));
+        assert_eq!(info[0].primary_language.as_deref(), Some("Vim script"));
+        assert_eq!(info[0].open_issues, 3);
+        let release = info[0].latest_release.as_ref().unwrap();
+        assert_eq!(release.tag_name, "0.1.0");
+        assert_eq!(
+            release.published_at.unwrap().format("%Y-%m-%d").to_string(),
+            "2014-01-02"
+        );
+        assert_eq!(info[1].requested, "Moll/vim-bbye");
+        assert!(info[1].is_fork);
+        assert!(info[1].latest_release.is_none());
+        assert!(info[1].pushed_at.is_none());
+        assert_eq!(lookup.not_found, vec!["gone/plugin".to_string()]);
+
+        let response: BatchResponse = serde_json::from_str(
+            r#"{ "data": null, "errors": [{ "type": "RATE_LIMITED", "message": "API rate limit exceeded" }] }"#,
+        )?;
+        assert!(convert_github_info(&names, response).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_ok() -> Result<()> {
+        init();
+
+        let body = r#"{
+            "data": {
+                "rateLimit": { "limit": 5000, "cost": 1, "remaining": 10, "resetAt": "2100-01-01T00:00:00Z" },
+                "r0": {
+                    "nameWithOwner": "neoclide/coc.nvim",
+                    "description": null,
+                    "isArchived": false,
+                    "isFork": false,
+                    "licenseInfo": null,
+                    "primaryLanguage": { "name": "TypeScript" },
+                    "stargazers": { "totalCount": 1 },
+                    "issues": { "totalCount": 2 },
+                    "releases": { "nodes": [] },
+                    "updatedAt": "2020-01-01T00:00:00Z",
+                    "pushedAt": "2020-01-01T00:00:00Z"
+                }
+            }
+        }"#;
+        let base_url = crate::hosting::mock::serve(vec![("/graphql", 200, body.to_string())]);
+        let github = GitHub {
+            base_url: format!("{}/graphql", base_url),
+            token: Some("token".to_string()),
+        };
+        let remotes = vec![RemoteUrl::parse("git@github.com:old-owner/coc.nvim.git")?];
+        let lookup = github.fetch(&remotes)?;
+        assert_eq!(lookup.repositories[0].requested, "old-owner/coc.nvim");
+        assert_eq!(lookup.repositories[0].name_with_owner, "neoclide/coc.nvim");
+        assert!(lookup.rate_limit.unwrap().is_low(chrono::Utc::now()));
+
+        let anonymous = GitHub {
+            base_url,
+            token: None,
+        };
+        assert!(!anonymous.is_available());
+        Ok(())
+    }
+
+    #[test]
+    fn release_notes_ok() -> Result<()> {
+        init();
+
+        let body = r#"{
+            "data": {
+                "repository": {
+                    "releases": {
+                        "nodes": [
+                            { "name": "", "description": "draft", "isDraft": true, "publishedAt": null, "tag": null },
+                            { "name": "Spring", "description": "* fix", "isDraft": false, "publishedAt": "2021-04-01T00:00:00Z", "tag": { "name": "v1.1.0" } },
+                            { "name": null, "description": null, "isDraft": false, "publishedAt": "2021-01-01T00:00:00Z", "tag": { "name": "v1.0.0" } }
+                        ]
+                    }
+                }
+            }
+        }"#;
+        let base_url = crate::hosting::mock::serve(vec![("/graphql", 200, body.to_string())]);
+        let github = GitHub {
+            base_url: format!("{}/graphql", base_url),
+            token: Some("token".to_string()),
+        };
+        let remote = RemoteUrl::parse("https://github.com/owner/plugin")?;
+        let notes = github.release_notes(&remote)?;
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].tag_name, "v1.1.0");
+        assert_eq!(notes[0].name.as_deref(), Some("Spring"));
+        assert_eq!(notes[0].body, "* fix");
+        assert_eq!(notes[1].body, "");
+        Ok(())
+    }
+
+    #[test]
+    fn build_query_ok() -> Result<()> {
+        let names: Vec<RemoteUrl> = (0..2)
+            .map(|i| RemoteUrl::parse(&format!("https://github.com/owner/plugin{}", i)))
+            .collect::<Result<_>>()?;
+        let (query, variables) = build_query(&names);
+        assert!(query.starts_with(
+            "query RepoBatch($o0: String!, $n0: String!, $o1: String!, $n1: String!)"
+        ));
+        assert!(query.contains("r1: repository(owner: $o1, name: $n1)"));
+        assert!(query.contains("fragment RepoInfo on Repository"));
+        assert!(query.contains("  ...RateLimitInfo\n"));
+        assert!(query.contains("fragment RateLimitInfo on Query"));
+        assert_eq!(query.matches("query ").count(), 1);
+        assert_eq!(variables["o1"], "owner");
+        assert_eq!(variables["n1"], "plugin1");
+        Ok(())
+    }
+}
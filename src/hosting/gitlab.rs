@@ -0,0 +1,161 @@
+use super::{encode, parse_time, HostingProvider, Lookup, Release, RepoInfo};
+use crate::repository::RemoteUrl;
+use anyhow::Result;
+use log::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub struct GitLab {
+    pub base_url: String,
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Project {
+    id: i64,
+    path_with_namespace: String,
+    description: Option<String>,
+    #[serde(default)]
+    archived: bool,
+    forked_from_project: Option<serde_json::Value>,
+    license: Option<License>,
+    star_count: i64,
+    #[serde(default)]
+    open_issues_count: i64,
+    last_activity_at: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct License {
+    key: Option<String>,
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ProjectRelease {
+    tag_name: String,
+    released_at: Option<String>,
+}
+
+impl HostingProvider for GitLab {
+    fn fetch(&self, remotes: &[RemoteUrl]) -> Result<Lookup> {
+        let mut lookup = Lookup::default();
+        for r in remotes {
+            match self.get_project(r)? {
+                Some(info) => lookup.repositories.push(info),
+                None => lookup.not_found.push(r.to_string()),
+            }
+        }
+        Ok(lookup)
+    }
+}
+
+impl GitLab {
+    fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        let client = crate::http::client()?;
+        let mut req = client.get(&format!("{}{}", self.base_url, path));
+        if let Some(token) = &self.token {
+            req = req.header("PRIVATE-TOKEN", token.as_str());
+        }
+        let res = req.send()?;
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body = res.error_for_status()?.json()?;
+        Ok(Some(body))
+    }
+
+    fn get_project(&self, remote: &RemoteUrl) -> Result<Option<RepoInfo>> {
+        let path = format!("/projects/{}", encode(&remote.name_with_owner()));
+        let project: Project = match self.get(&format!("{}?license=true", path))? {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+        debug!("{:?}", project);
+        let languages: HashMap<String, f64> = self
+            .get(&format!("/projects/{}/languages", project.id))?
+            .unwrap_or_default();
+        let releases: Vec<ProjectRelease> = self
+            .get(&format!("/projects/{}/releases?per_page=1", project.id))?
+            .unwrap_or_default();
+        let last_activity_at = parse_time(&project.last_activity_at);
+        Ok(Some(RepoInfo {
+            requested: remote.to_string(),
+            name_with_owner: project.path_with_namespace,
+            description: project.description.filter(|d| !d.is_empty()),
+            is_archived: project.archived,
+            is_fork: project.forked_from_project.is_some(),
+            license: project.license.map(|l| l.key.unwrap_or(l.name)),
+            primary_language: languages
+                .into_iter()
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(l, _)| l),
+            stargazers: project.star_count,
+            open_issues: project.open_issues_count,
+            updated_at: last_activity_at.unwrap_or_default(),
+            pushed_at: last_activity_at,
+            latest_release: releases.into_iter().next().map(|r| Release {
+                tag_name: r.tag_name,
+                published_at: r.released_at.as_deref().and_then(parse_time),
+            }),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetch_ok() -> Result<()> {
+        let project = r#"{
+            "id": 42,
+            "path_with_namespace": "group/sub/plugin",
+            "description": "",
+            "archived": true,
+            "forked_from_project": { "id": 1 },
+            "license": { "key": "mit", "name": "MIT License" },
+            "star_count": 7,
+            "open_issues_count": 3,
+            "last_activity_at": "2020-05-01T10:00:00.000Z"
+        }"#;
+        let base_url = crate::hosting::mock::serve(vec![
+            (
+                "/api/v4/projects/group%2Fsub%2Fplugin",
+                200,
+                project.to_string(),
+            ),
+            (
+                "/api/v4/projects/42/languages",
+                200,
+                r#"{ "Vim Script": 80.5, "Shell": 19.5 }"#.to_string(),
+            ),
+            (
+                "/api/v4/projects/42/releases",
+                200,
+                r#"[{ "tag_name": "v1.2.0", "released_at": "2020-04-01T00:00:00.000Z" }]"#
+                    .to_string(),
+            ),
+        ]);
+        let gitlab = GitLab {
+            base_url: format!("{}/api/v4", base_url),
+            token: None,
+        };
+        let remotes = vec![
+            RemoteUrl::parse("https://gitlab.com/group/sub/plugin.git")?,
+            RemoteUrl::parse("https://gitlab.com/group/gone.git")?,
+        ];
+        let lookup = gitlab.fetch(&remotes)?;
+        let info = &lookup.repositories[0];
+        assert_eq!(info.requested, "gitlab.com/group/sub/plugin");
+        assert!(info.is_archived);
+        assert!(info.is_fork);
+        assert_eq!(info.description, None);
+        assert_eq!(info.license.as_deref(), Some("mit"));
+        assert_eq!(info.primary_language.as_deref(), Some("Vim Script"));
+        assert_eq!(info.open_issues, 3);
+        assert_eq!(info.latest_release.as_ref().unwrap().tag_name, "v1.2.0");
+        assert_eq!(lookup.not_found, vec!["gitlab.com/group/gone".to_string()]);
+        Ok(())
+    }
+}
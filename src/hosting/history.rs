@@ -0,0 +1,292 @@
+use super::RepoInfo;
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const SPARKS: &[char] = &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+// The file is compacted once it grows past this
+const COMPACT_SIZE: u64 = 1024 * 1024;
+// Readings older than this are dropped on compaction
+const MAX_AGE_DAYS: i64 = 730;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reading {
+    pub at: NaiveDateTime,
+    pub host: String,
+    pub name_with_owner: String,
+    pub stars: i64,
+    pub pushed_at: Option<NaiveDateTime>,
+    pub open_issues: i64,
+}
+
+impl Reading {
+    pub fn new(host: &str, info: &RepoInfo, at: NaiveDateTime) -> Reading {
+        Reading {
+            at,
+            host: host.to_string(),
+            name_with_owner: info.name_with_owner.to_string(),
+            stars: info.stargazers,
+            pushed_at: info.pushed_at,
+            open_issues: info.open_issues,
+        }
+    }
+}
+
+// Readings per repository, oldest first
+#[derive(Debug, Default)]
+pub struct History {
+    readings: HashMap<String, Vec<Reading>>,
+}
+
+impl History {
+    pub fn load(path: &Path) -> Result<History> {
+        let mut history = History::default();
+        if !path.exists() {
+            return Ok(history);
+        }
+        for line in std::fs::read_to_string(path)?.lines() {
+            match serde_json::from_str::<Reading>(line) {
+                Ok(r) => history
+                    .readings
+                    .entry(key(&r.host, &r.name_with_owner))
+                    .or_default()
+                    .push(r),
+                Err(e) => debug!("skipping history line {:?}: {}", line, e),
+            }
+        }
+        for readings in history.readings.values_mut() {
+            readings.sort_by_key(|r| r.at);
+        }
+        Ok(history)
+    }
+
+    pub fn get(&self, host: &str, name_with_owner: &str) -> &[Reading] {
+        self.readings
+            .get(&key(host, name_with_owner))
+            .map(|r| r.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+fn key(host: &str, name_with_owner: &str) -> String {
+    format!("{}/{}", host, name_with_owner).to_lowercase()
+}
+
+// One json line per reading, so runs only ever append until the file needs compacting
+pub fn append(path: &Path, readings: &[Reading]) -> Result<()> {
+    if readings.is_empty() {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut s = String::new();
+    for r in readings {
+        s.push_str(&serde_json::to_string(r)?);
+        s.push('\n');
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(s.as_bytes())?;
+    if std::fs::metadata(path)?.len() > COMPACT_SIZE {
+        let now = readings.iter().map(|r| r.at).max().unwrap_or_default();
+        compact(path, now)?;
+    }
+    Ok(())
+}
+
+// Keeps the last reading of each day per repository and drops those older than MAX_AGE_DAYS
+pub fn compact(path: &Path, now: NaiveDateTime) -> Result<()> {
+    let history = History::load(path)?;
+    let oldest = now - Duration::days(MAX_AGE_DAYS);
+    let mut kept: Vec<&Reading> = vec![];
+    for readings in history.readings.values() {
+        for (i, r) in readings.iter().enumerate() {
+            let last_of_day = readings
+                .get(i + 1)
+                .is_none_or(|next| next.at.date() != r.at.date());
+            if r.at >= oldest && last_of_day {
+                kept.push(r);
+            }
+        }
+    }
+    kept.sort_by_key(|r| r.at);
+    debug!("compacting history to {} readings", kept.len());
+
+    let mut s = String::new();
+    for r in kept {
+        s.push_str(&serde_json::to_string(r)?);
+        s.push('\n');
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    std::fs::write(&tmp, s)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+pub fn path() -> Result<PathBuf> {
+    let mut path = crate::config::state_dir()?;
+    path.push("history.jsonl");
+    Ok(path)
+}
+
+// Change since the last reading taken before `since`; None until the history reaches back that far
+pub fn delta<F>(readings: &[Reading], since: NaiveDateTime, value: F) -> Option<i64>
+where
+    F: Fn(&Reading) -> i64,
+{
+    let latest = readings.last()?;
+    let base = readings.iter().rev().find(|r| r.at <= since)?;
+    Some(value(latest) - value(base))
+}
+
+// Like delta, but falls back to the earliest reading while the history is shorter than the window
+pub fn delta_or_earliest<F>(readings: &[Reading], since: NaiveDateTime, value: F) -> Option<i64>
+where
+    F: Fn(&Reading) -> i64,
+{
+    if readings.len() < 2 {
+        return None;
+    }
+    delta(readings, since, &value).or_else(|| Some(value(readings.last()?) - value(&readings[0])))
+}
+
+// Stars at the end of each of `width` equal slices of since..now, carrying the last reading forward
+pub fn trend(
+    readings: &[Reading],
+    since: NaiveDateTime,
+    now: NaiveDateTime,
+    width: i32,
+) -> Vec<i64> {
+    let step = (now - since) / width;
+    (1..=width)
+        .filter_map(|i| {
+            let t = since + step * i;
+            readings.iter().rev().find(|r| r.at <= t).map(|r| r.stars)
+        })
+        .collect()
+}
+
+pub fn sparkline(values: &[i64]) -> String {
+    let min = values.iter().min().copied().unwrap_or_default();
+    let max = values.iter().max().copied().unwrap_or_default();
+    values
+        .iter()
+        .map(|v| match max - min {
+            0 => SPARKS[0],
+            range => SPARKS[((v - min) * (SPARKS.len() as i64 - 1) / range) as usize],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn reading(at: &str, stars: i64) -> Reading {
+        Reading {
+            at: NaiveDateTime::parse_from_str(at, "%Y-%m-%d %H:%M:%S").unwrap(),
+            host: "github.com".to_string(),
+            name_with_owner: "Owner/plugin".to_string(),
+            stars,
+            pushed_at: None,
+            open_issues: 0,
+        }
+    }
+
+    #[test]
+    fn append_and_load_ok() -> Result<()> {
+        let dir = temp_dir("dotplugs_history");
+        let path = PathBuf::from(format!("{}/history.jsonl", dir));
+        append(&path, &[reading("2020-06-03 00:00:00", 12)])?;
+        append(
+            &path,
+            &[
+                reading("2020-06-01 00:00:00", 10),
+                reading("2020-06-02 00:00:00", 11),
+            ],
+        )?;
+        let history = History::load(&path)?;
+        let readings = history.get("github.com", "owner/Plugin");
+        assert_eq!(
+            readings.iter().map(|r| r.stars).collect::<Vec<_>>(),
+            vec![10, 11, 12]
+        );
+        assert!(history.get("github.com", "owner/other").is_empty());
+        std::fs::remove_dir_all(&dir).unwrap_or(());
+        Ok(())
+    }
+
+    #[test]
+    fn delta_and_trend_ok() -> Result<()> {
+        let readings = vec![
+            reading("2020-06-01 00:00:00", 10),
+            reading("2020-06-05 00:00:00", 20),
+            reading("2020-06-09 00:00:00", 15),
+        ];
+        let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S");
+        assert_eq!(
+            delta(&readings, at("2020-06-06 00:00:00")?, |r| r.stars),
+            Some(-5)
+        );
+        assert_eq!(
+            delta(&readings, at("2020-06-02 00:00:00")?, |r| r.stars),
+            Some(5)
+        );
+        assert_eq!(
+            delta(&readings, at("2020-05-31 00:00:00")?, |r| r.stars),
+            None
+        );
+        assert_eq!(
+            delta_or_earliest(&readings, at("2020-05-31 00:00:00")?, |r| r.stars),
+            Some(5)
+        );
+        assert_eq!(
+            delta_or_earliest(&readings[..1], at("2020-05-31 00:00:00")?, |r| r.stars),
+            None
+        );
+
+        let values = trend(
+            &readings,
+            at("2020-06-01 00:00:00")?,
+            at("2020-06-09 00:00:00")?,
+            4,
+        );
+        assert_eq!(values, vec![10, 20, 20, 15]);
+        assert_eq!(sparkline(&values), "▁██▄");
+        assert_eq!(sparkline(&[3, 3]), "▁▁");
+        Ok(())
+    }
+
+    #[test]
+    fn compact_ok() -> Result<()> {
+        let dir = temp_dir("dotplugs_history");
+        let path = PathBuf::from(format!("{}/history.jsonl", dir));
+        append(
+            &path,
+            &[
+                reading("2018-01-01 00:00:00", 1),
+                reading("2020-06-01 00:00:00", 10),
+                reading("2020-06-01 12:00:00", 11),
+                reading("2020-06-02 00:00:00", 12),
+            ],
+        )?;
+        let now = NaiveDateTime::parse_from_str("2020-06-02 00:00:00", "%Y-%m-%d %H:%M:%S")?;
+        compact(&path, now)?;
+        let history = History::load(&path)?;
+        let readings = history.get("github.com", "owner/Plugin");
+        assert_eq!(
+            readings.iter().map(|r| r.stars).collect::<Vec<_>>(),
+            vec![11, 12]
+        );
+        std::fs::remove_dir_all(&dir).unwrap_or(());
+        Ok(())
+    }
+}
@@ -0,0 +1,292 @@
+use crate::repository::RemoteUrl;
+use anyhow::{anyhow, Result};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseNote {
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub published_at: Option<NaiveDateTime>,
+    pub body: String,
+}
+
+// Only this many releases are requested from the provider
+pub const RELEASE_COUNT: usize = 20;
+
+// Where the checkout stands and what an update would bring in
+#[derive(Debug, Default)]
+pub struct Range {
+    pub current_tag: Option<String>,
+    pub since: NaiveDateTime,
+    // Tags on the commits an update brings in; None takes every release after the checkout
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default)]
+pub struct Releases {
+    pub notes: Vec<ReleaseNote>,
+    // The oldest fetched release is still inside the range
+    pub truncated: bool,
+}
+
+pub fn fetch(remote: &RemoteUrl, range: &Range) -> Result<Releases> {
+    let host = match remote.host.as_deref() {
+        Some(h) => h,
+        None => return Ok(Releases::default()),
+    };
+    let provider = match super::provider(host)? {
+        Some(p) => p,
+        None => return Ok(Releases::default()),
+    };
+    if !provider.is_available() {
+        return Err(anyhow!("no token found for {}", host));
+    }
+    Ok(select(provider.release_notes(remote)?, range))
+}
+
+// Notes come newest first. With the tags of the update known, releases are picked by tag since
+// they are published some time after the commit they tag; otherwise by date down to the checkout.
+pub fn select(notes: Vec<ReleaseNote>, range: &Range) -> Releases {
+    let fetched = notes.len();
+    let mut reached_checkout = false;
+    let mut selected = vec![];
+    for n in notes {
+        if range.current_tag.as_deref() == Some(n.tag_name.as_str())
+            || n.published_at.is_some_and(|d| d <= range.since)
+        {
+            reached_checkout = true;
+            if range.tags.is_none() {
+                break;
+            }
+        }
+        if range.tags.as_ref().is_none_or(|t| t.contains(&n.tag_name)) {
+            selected.push(n);
+        }
+    }
+    Releases {
+        notes: selected,
+        truncated: fetched >= RELEASE_COUNT && !reached_checkout,
+    }
+}
+
+// Release bodies are GitHub flavored markdown; the terminal gets the text without the markup
+pub fn plain_text(markdown: &str) -> String {
+    let markdown = strip_comments(markdown);
+    let mut lines: Vec<String> = vec![];
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            continue;
+        }
+        let line = if trimmed.starts_with('#') {
+            trimmed.trim_start_matches('#').trim_start().to_string()
+        } else if let Some(item) = trimmed
+            .strip_prefix("* ")
+            .or_else(|| trimmed.strip_prefix("+ "))
+        {
+            let indent = line.len() - trimmed.len();
+            format!("{}- {}", " ".repeat(indent), item)
+        } else {
+            line.to_string()
+        };
+        let line = strip_inline(&line);
+        if line.is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+fn strip_comments(s: &str) -> String {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("<!--") {
+        out.push_str(&rest[..start]);
+        rest = match rest[start..].find("-->") {
+            Some(end) => &rest[start + end + 3..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+// Links and images keep their text, emphasis markers, code ticks and html tags go away
+fn strip_inline(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '[' || (c == '!' && chars.get(i + 1) == Some(&'[')) {
+            let open = if c == '!' { i + 1 } else { i };
+            if let Some(len) = link_len(&chars[open..]) {
+                let text_end = chars[open..].iter().position(|c| *c == ']').unwrap() + open;
+                out.extend(&chars[open + 1..text_end]);
+                i = open + len;
+                continue;
+            }
+        }
+        if c == '<' {
+            if let Some(end) = chars[i..].iter().position(|c| *c == '>') {
+                let inner: String = chars[i + 1..i + end].iter().collect();
+                if inner.starts_with("http://") || inner.starts_with("https://") {
+                    out.push_str(&inner);
+                    i += end + 1;
+                    continue;
+                }
+                if inner.starts_with('/')
+                    || inner
+                        .chars()
+                        .next()
+                        .is_some_and(|c| c.is_ascii_alphabetic())
+                {
+                    i += end + 1;
+                    continue;
+                }
+            }
+        }
+        if c == '`' || (c == '*' && chars.get(i + 1) == Some(&'*')) {
+            i += if c == '*' { 2 } else { 1 };
+            continue;
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+// Length of `[text](url)` at the start of chars
+fn link_len(chars: &[char]) -> Option<usize> {
+    let close = chars.iter().position(|c| *c == ']')?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    let end = chars[close..].iter().position(|c| *c == ')')? + close;
+    Some(end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(tag: &str, date: &str) -> ReleaseNote {
+        ReleaseNote {
+            tag_name: tag.to_string(),
+            name: None,
+            published_at: crate::hosting::parse_time(date),
+            body: "".to_string(),
+        }
+    }
+
+    fn tags(releases: Releases) -> Vec<String> {
+        releases.notes.into_iter().map(|n| n.tag_name).collect()
+    }
+
+    #[test]
+    fn select_ok() {
+        let notes = vec![
+            note("v2.0.0", "2021-03-01T00:00:00Z"),
+            note("v1.2.0", "2021-02-01T00:00:00Z"),
+            note("v1.1.0", "2021-01-01T00:00:00Z"),
+            note("v1.0.0", "2020-12-01T00:00:00Z"),
+        ];
+        let range = Range {
+            current_tag: Some("v1.0.0".to_string()),
+            tags: Some(vec!["v1.1.0".to_string(), "v1.2.0".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            tags(select(notes.clone(), &range)),
+            vec!["v1.2.0", "v1.1.0"]
+        );
+
+        let range = Range {
+            since: crate::hosting::parse_time("2021-01-15T00:00:00Z").unwrap(),
+            ..Default::default()
+        };
+        let releases = select(notes, &range);
+        assert!(!releases.truncated);
+        assert_eq!(tags(releases), vec!["v2.0.0", "v1.2.0"]);
+    }
+
+    #[test]
+    fn select_by_tags_of_the_update() {
+        // A branch-tracked plugin: no current tag, the release of the tip commit comes a day later,
+        // and v1.3.0 in the update has no release at all
+        let notes = vec![
+            note("v2.0.0", "2021-04-01T00:00:00Z"),
+            note("v1.2.0", "2021-02-02T00:00:00Z"),
+            note("v1.1.0", "2021-01-01T00:00:00Z"),
+            note("v1.0.0", "2020-12-01T00:00:00Z"),
+        ];
+        let range = Range {
+            since: crate::hosting::parse_time("2020-12-15T00:00:00Z").unwrap(),
+            tags: Some(vec![
+                "v1.1.0".to_string(),
+                "v1.2.0".to_string(),
+                "v1.3.0".to_string(),
+            ]),
+            ..Default::default()
+        };
+        let releases = select(notes, &range);
+        assert!(!releases.truncated);
+        assert_eq!(tags(releases), vec!["v1.2.0", "v1.1.0"]);
+    }
+
+    #[test]
+    fn select_truncated() {
+        let notes: Vec<ReleaseNote> = (0..RELEASE_COUNT)
+            .rev()
+            .map(|i| {
+                note(
+                    &format!("v1.{}.0", i + 1),
+                    &format!("2021-01-{:02}T00:00:00Z", i + 1),
+                )
+            })
+            .collect();
+        let range = Range {
+            current_tag: Some("v1.0.0".to_string()),
+            ..Default::default()
+        };
+        let releases = select(notes.clone(), &range);
+        assert!(releases.truncated);
+        assert_eq!(releases.notes.len(), RELEASE_COUNT);
+
+        let range = Range {
+            current_tag: Some("v1.1.0".to_string()),
+            ..Default::default()
+        };
+        assert!(!select(notes, &range).truncated);
+    }
+
+    #[test]
+    fn plain_text_ok() {
+        let markdown = "## What's Changed\r\n\
+            <!-- Release notes generated by GitHub -->\r\n\
+            * Fix **crash** on `:q` by @user in [#12](https://github.com/o/p/pull/12)\r\n\
+            \r\n\
+            \r\n\
+            ![screenshot](https://example.com/a.png)\r\n\
+            ```vim\r\n\
+            let g:foo = 1\r\n\
+            ```\r\n\
+            Full Changelog: <https://github.com/o/p/compare/v1...v2><br>\r\n";
+        assert_eq!(
+            plain_text(markdown),
+            "What's Changed\n\
+             \n\
+             - Fix crash on :q by @user in #12\n\
+             \n\
+             screenshot\n\
+             let g:foo = 1\n\
+             Full Changelog: https://github.com/o/p/compare/v1...v2"
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use super::{parse_time, HostingProvider, Lookup, RepoInfo};
+use crate::repository::RemoteUrl;
+use anyhow::{anyhow, Result};
+use log::*;
+use serde::Deserialize;
+
+const QUERY: &str = "query($owner: String!, $name: String!) {
+  user(username: $owner) {
+    repository(name: $name) {
+      name
+      description
+      updated
+      owner {
+        canonicalName
+      }
+    }
+  }
+}";
+
+// git.sr.ht's GraphQL API does not answer anonymous requests
+pub struct Sourcehut {
+    pub base_url: String,
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Response {
+    data: Option<Data>,
+    #[serde(default)]
+    errors: Vec<Error>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Data {
+    user: Option<User>,
+}
+
+#[derive(Deserialize, Debug)]
+struct User {
+    repository: Option<Repository>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Repository {
+    name: String,
+    description: Option<String>,
+    updated: String,
+    owner: Owner,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Owner {
+    canonical_name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Error {
+    message: String,
+}
+
+impl HostingProvider for Sourcehut {
+    fn is_available(&self) -> bool {
+        self.token.is_some()
+    }
+
+    fn fetch(&self, remotes: &[RemoteUrl]) -> Result<Lookup> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or(anyhow!("sourcehut token not found"))?;
+        let mut lookup = Lookup::default();
+        for r in remotes {
+            match self.get_repository(r, token)? {
+                Some(info) => lookup.repositories.push(info),
+                None => lookup.not_found.push(r.to_string()),
+            }
+        }
+        Ok(lookup)
+    }
+}
+
+impl Sourcehut {
+    fn get_repository(&self, remote: &RemoteUrl, token: &str) -> Result<Option<RepoInfo>> {
+        let body = serde_json::json!({
+            "query": QUERY,
+            "variables": {
+                "owner": remote.owner().trim_start_matches('~'),
+                "name": remote.name,
+            },
+        });
+        let client = crate::http::client()?;
+        let response: Response = client
+            .post(&self.base_url)
+            .bearer_auth(token)
+            .json(&body)
+            .send()?
+            .error_for_status()?
+            .json()?;
+        debug!("{:?}", response);
+
+        let repo = response
+            .data
+            .and_then(|d| d.user)
+            .and_then(|u| u.repository);
+        let repo = match (repo, response.errors.first()) {
+            (Some(r), _) => r,
+            (None, Some(e)) if !e.message.contains("no rows") => {
+                return Err(anyhow!("{}", e.message))
+            }
+            (None, _) => return Ok(None),
+        };
+        let updated = parse_time(&repo.updated);
+        Ok(Some(RepoInfo {
+            requested: remote.to_string(),
+            name_with_owner: format!("{}/{}", repo.owner.canonical_name, repo.name),
+            description: repo.description.filter(|d| !d.is_empty()),
+            is_archived: false,
+            is_fork: false,
+            license: None,
+            primary_language: None,
+            stargazers: 0,
+            open_issues: 0,
+            updated_at: updated.unwrap_or_default(),
+            pushed_at: updated,
+            latest_release: None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetch_ok() -> Result<()> {
+        let body = r#"{
+            "data": {
+                "user": {
+                    "repository": {
+                        "name": "plugin.vim",
+                        "description": "A plugin",
+                        "updated": "2022-01-02T03:04:05.123456Z",
+                        "owner": { "canonicalName": "~user" }
+                    }
+                }
+            }
+        }"#;
+        let base_url = crate::hosting::mock::serve(vec![("/query", 200, body.to_string())]);
+        let sourcehut = Sourcehut {
+            base_url: format!("{}/query", base_url),
+            token: Some("token".to_string()),
+        };
+        let remotes = vec![RemoteUrl::parse("https://git.sr.ht/~user/plugin.vim")?];
+        let lookup = sourcehut.fetch(&remotes)?;
+        let info = &lookup.repositories[0];
+        assert_eq!(info.requested, "git.sr.ht/~user/plugin.vim");
+        assert_eq!(info.name_with_owner, "~user/plugin.vim");
+        assert_eq!(
+            info.pushed_at.unwrap().format("%Y-%m-%d").to_string(),
+            "2022-01-02"
+        );
+
+        let anonymous = Sourcehut {
+            base_url,
+            token: None,
+        };
+        assert!(!anonymous.is_available());
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::config::HttpConfig;
+use anyhow::{anyhow, Context, Result};
+use lazy_static::lazy_static;
+use reqwest::Url;
+
+lazy_static! {
+    static ref CLIENT: std::result::Result<reqwest::Client, String> =
+        build().map_err(|e| format!("{:?}", e));
+}
+
+// Built once per run and shared by every hosting provider
+pub fn client() -> Result<&'static reqwest::Client> {
+    CLIENT.as_ref().map_err(|e| anyhow!("{}", e))
+}
+
+fn build() -> Result<reqwest::Client> {
+    let config = &crate::config::get()?.http;
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(config.connect_timeout())
+        .timeout(config.timeout());
+    for path in &config.ca_certs {
+        let pem =
+            std::fs::read_to_string(path).with_context(|| format!("while reading {:?}", path))?;
+        for cert in split_pem(&pem) {
+            let cert = reqwest::Certificate::from_pem(cert.as_bytes())
+                .with_context(|| format!("while reading {:?}", path))?;
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    let proxies = Proxies::new(config, |name| std::env::var(name).ok())?;
+    builder = builder.proxy(reqwest::Proxy::custom(move |url| proxies.get(url)));
+    Ok(builder.build()?)
+}
+
+// A CA bundle holds several certificates, but from_pem only reads the first one
+fn split_pem(pem: &str) -> Vec<String> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    pem.split(BEGIN)
+        .skip(1)
+        .map(|c| format!("{}{}", BEGIN, c))
+        .collect()
+}
+
+// http(s)_proxy, all_proxy and no_proxy as curl reads them; a proxy in config.toml wins
+#[derive(Debug, Default)]
+struct Proxies {
+    http: Option<Url>,
+    https: Option<Url>,
+    no_proxy: Vec<String>,
+}
+
+impl Proxies {
+    fn new<F>(config: &HttpConfig, env: F) -> Result<Proxies>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let var = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|n| env(n).or_else(|| env(&n.to_uppercase())))
+                .filter(|v| !v.is_empty())
+        };
+        let parse = |s: Option<String>| -> Result<Option<Url>> {
+            match s {
+                Some(s) => Ok(Some(
+                    Url::parse(&s).map_err(|e| anyhow!("invalid proxy {}: {}", s, e))?,
+                )),
+                None => Ok(None),
+            }
+        };
+        let all = config.proxy.clone().or_else(|| var(&["all_proxy"]));
+        let mut no_proxy: Vec<String> = var(&["no_proxy"])
+            .map(|v| v.split(',').map(|h| h.trim().to_string()).collect())
+            .unwrap_or_default();
+        no_proxy.extend(config.no_proxy.iter().cloned());
+        no_proxy.extend(vec!["localhost".to_string(), "127.0.0.1".to_string()]);
+        Ok(Proxies {
+            http: parse(
+                config
+                    .proxy
+                    .clone()
+                    .or_else(|| var(&["http_proxy"]))
+                    .or_else(|| all.clone()),
+            )?,
+            https: parse(
+                config
+                    .proxy
+                    .clone()
+                    .or_else(|| var(&["https_proxy"]))
+                    .or(all),
+            )?,
+            no_proxy,
+        })
+    }
+
+    fn get(&self, url: &Url) -> Option<Url> {
+        let host = url.host_str()?;
+        let bypass = self.no_proxy.iter().any(|n| {
+            let n = n.trim_start_matches('.');
+            n == "*" || host == n || host.ends_with(&format!(".{}", n))
+        });
+        if bypass {
+            return None;
+        }
+        match url.scheme() {
+            "https" => self.https.clone(),
+            _ => self.http.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn proxies_ok() -> Result<()> {
+        let vars: HashMap<&str, &str> = vec![
+            ("HTTPS_PROXY", "http://proxy.example.com:8080"),
+            ("no_proxy", "github.example.com, .internal"),
+        ]
+        .into_iter()
+        .collect();
+        let env = |name: &str| vars.get(name).map(|v| v.to_string());
+        let proxies = Proxies::new(&HttpConfig::default(), env)?;
+        let url = |s| Url::parse(s).unwrap();
+        assert_eq!(
+            proxies.get(&url("https://api.github.com/graphql")),
+            Some(url("http://proxy.example.com:8080"))
+        );
+        assert_eq!(proxies.get(&url("http://codeberg.org/api/v1")), None);
+        assert_eq!(proxies.get(&url("https://github.example.com/api")), None);
+        assert_eq!(proxies.get(&url("https://git.internal/api")), None);
+        assert_eq!(proxies.get(&url("http://127.0.0.1:8080/graphql")), None);
+
+        let config = HttpConfig {
+            proxy: Some("http://config.example.com:3128".to_string()),
+            ..Default::default()
+        };
+        let proxies = Proxies::new(&config, env)?;
+        assert_eq!(
+            proxies.get(&url("http://codeberg.org/api/v1")),
+            Some(url("http://config.example.com:3128"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn split_pem_ok() {
+        let pem = "# corporate roots\n-----BEGIN CERTIFICATE-----\nAAA\n-----END CERTIFICATE-----\n-----BEGIN CERTIFICATE-----\nBBB\n-----END CERTIFICATE-----\n";
+        let certs = split_pem(pem);
+        assert_eq!(certs.len(), 2);
+        assert!(certs[1].starts_with("-----BEGIN CERTIFICATE-----\nBBB"));
+    }
+}
@@ -0,0 +1,256 @@
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use termion::clear;
+
+const GRACE_PERIOD: Duration = Duration::from_secs(10);
+const KILL_DELAY: Duration = Duration::from_secs(3);
+
+lazy_static! {
+    static ref INTERRUPTED: AtomicBool = AtomicBool::new(false);
+    static ref BUSY: AtomicUsize = AtomicUsize::new(0);
+    static ref CHILDREN: Mutex<HashSet<u32>> = Mutex::new(HashSet::new());
+}
+
+pub struct Busy;
+
+impl Drop for Busy {
+    fn drop(&mut self) {
+        BUSY.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub fn install() -> Result<()> {
+    ctrlc::set_handler(on_interrupt)?;
+    Ok(())
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+pub fn check() -> Result<()> {
+    if is_interrupted() {
+        return Err(anyhow!("Interrupted"));
+    }
+    Ok(())
+}
+
+// Ctrl-C exits immediately unless a Busy guard is alive
+pub fn busy() -> Busy {
+    BUSY.fetch_add(1, Ordering::SeqCst);
+    Busy
+}
+
+// Children get their own process group so that the terminal's SIGINT does not hit them
+pub fn spawn(cmd: &mut Command) -> Result<Child> {
+    spawn_with_stdin(cmd, Stdio::null())
+}
+
+fn spawn_with_stdin(cmd: &mut Command, stdin: Stdio) -> Result<Child> {
+    let child = cmd.stdin(stdin).process_group(0).spawn()?;
+    CHILDREN.lock().unwrap().insert(child.id());
+    Ok(child)
+}
+
+pub fn output(cmd: &mut Command) -> Result<Output> {
+    let child = spawn(cmd.stdout(Stdio::piped()).stderr(Stdio::piped()))?;
+    let id = child.id();
+    let output = child.wait_with_output();
+    CHILDREN.lock().unwrap().remove(&id);
+    Ok(output?)
+}
+
+#[derive(Debug)]
+pub struct TimedOut(pub Duration);
+
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "timed out after {}s", self.0.as_secs())
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+pub fn output_with_timeout(cmd: &mut Command, timeout: Duration) -> Result<Output> {
+    let child = spawn(cmd.stdout(Stdio::piped()).stderr(Stdio::piped()))?;
+    wait_with_timeout(child, timeout)
+}
+
+// Same as output_with_timeout, with `input` written to the child's stdin
+pub fn output_with_input(cmd: &mut Command, input: &[u8], timeout: Duration) -> Result<Output> {
+    let mut child = spawn_with_stdin(
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped()),
+        Stdio::piped(),
+    )?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A child that exits without reading is reported through its status
+        stdin.write_all(input).unwrap_or(());
+    }
+    wait_with_timeout(child, timeout)
+}
+
+fn wait_with_timeout(mut child: Child, timeout: Duration) -> Result<Output> {
+    let readers: Vec<_> = vec![
+        child
+            .stdout
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send>),
+        child
+            .stderr
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .map(|r| {
+        std::thread::spawn(move || {
+            let mut buf = vec![];
+            if let Some(mut r) = r {
+                r.read_to_end(&mut buf).unwrap_or(0);
+            }
+            buf
+        })
+    })
+    .collect();
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if start.elapsed() >= timeout {
+            terminate(&mut child)?;
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    forget(&child);
+
+    let mut outputs = readers.into_iter().map(|r| r.join().unwrap_or_default());
+    match status {
+        Some(status) => Ok(Output {
+            status,
+            stdout: outputs.next().unwrap_or_default(),
+            stderr: outputs.next().unwrap_or_default(),
+        }),
+        None => Err(TimedOut(timeout).into()),
+    }
+}
+
+// TERM lets git remove its index.lock; KILL only what is still running after KILL_DELAY
+fn terminate(child: &mut Child) -> Result<()> {
+    kill_group(child.id(), "-TERM");
+    let start = Instant::now();
+    while start.elapsed() < KILL_DELAY {
+        if child.try_wait()?.is_some() {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    kill_group(child.id(), "-KILL");
+    child.wait()?;
+    Ok(())
+}
+
+pub fn forget(child: &Child) {
+    CHILDREN.lock().unwrap().remove(&child.id());
+}
+
+fn on_interrupt() {
+    eprint!("\r{}", clear::CurrentLine);
+    if BUSY.load(Ordering::SeqCst) == 0 {
+        kill_children();
+        // exit skips destructors, so the lock file has to go first
+        crate::lock::remove_held();
+        std::process::exit(130);
+    }
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        eprintln!("Killing running git processes");
+        kill_children();
+        return;
+    }
+    eprintln!(
+        "Interrupted, waiting up to {}s for running git processes (press Ctrl-C again to kill them)",
+        GRACE_PERIOD.as_secs()
+    );
+    std::thread::spawn(|| {
+        std::thread::sleep(GRACE_PERIOD);
+        kill_children();
+    });
+}
+
+fn kill_children() {
+    let children: Vec<u32> = CHILDREN.lock().unwrap().iter().cloned().collect();
+    for pid in children {
+        kill_group(pid, "-TERM");
+    }
+}
+
+fn kill_group(pid: u32, signal: &str) {
+    Command::new("kill")
+        .args([signal, "--", &format!("-{}", pid)])
+        .stderr(Stdio::null())
+        .status()
+        .map(|_| ())
+        .unwrap_or(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_with_timeout_ok() -> Result<()> {
+        let output = output_with_timeout(
+            Command::new("sh").args(["-c", "echo out; echo err >&2"]),
+            Duration::from_secs(10),
+        )?;
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+
+        let err = output_with_timeout(
+            Command::new("sh").args(["-c", "sleep 5; echo done"]),
+            Duration::from_millis(200),
+        )
+        .unwrap_err();
+        assert!(err.is::<TimedOut>());
+
+        let marker = format!("{}.term", crate::test_util::temp_dir("dotplugs_interrupt"));
+        let script = format!("trap 'echo term > {}; exit 1' TERM; sleep 5 & wait", marker);
+        let err = output_with_timeout(
+            Command::new("sh").args(["-c", &script]),
+            Duration::from_millis(200),
+        )
+        .unwrap_err();
+        assert!(err.is::<TimedOut>());
+        assert_eq!(std::fs::read_to_string(&marker)?, "term\n");
+        std::fs::remove_file(&marker).unwrap_or(());
+        Ok(())
+    }
+
+    #[test]
+    fn output_with_input_ok() -> Result<()> {
+        let output = output_with_input(
+            &mut Command::new("cat"),
+            b"host=example.com\n",
+            Duration::from_secs(10),
+        )?;
+        assert_eq!(output.stdout, b"host=example.com\n");
+
+        let err = output_with_input(
+            Command::new("sh").args(["-c", "sleep 5"]),
+            b"",
+            Duration::from_millis(200),
+        )
+        .unwrap_err();
+        assert!(err.is::<TimedOut>());
+        Ok(())
+    }
+}
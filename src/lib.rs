@@ -1,43 +1,120 @@
+mod config;
+mod disk;
 mod display;
 mod git;
-mod github;
+mod hook;
+mod hosting;
+mod http;
+mod interrupt;
+mod lock;
 mod repository;
 mod subcommand;
+#[cfg(test)]
+mod test_util;
 
 use anyhow::Result;
 use env_logger::Target;
 
-pub fn check() -> Result<()> {
-    init_logger();
-    subcommand::checker::check()?;
+pub use lock::ProcessLock;
+pub use repository::Filter;
+pub use subcommand::changelog::LogOptions;
+pub use subcommand::cleaner::CleanOptions;
+pub use subcommand::housekeeper::GcOptions;
+pub use subcommand::remote_fixer::FixRemotesOptions;
+pub use subcommand::stats::StatsOptions;
+pub use subcommand::updater::UpdateOptions;
+
+pub fn check(filter: &Filter) -> Result<()> {
+    init();
+    subcommand::checker::check(filter)?;
     Ok(())
 }
 
-pub fn check_output_json() -> Result<()> {
-    init_logger();
-    let j = subcommand::checker::output_json()?;
+pub fn check_output_json(filter: &Filter) -> Result<()> {
+    init();
+    let j = subcommand::checker::output_json(filter)?;
     println!("{}", j);
     Ok(())
 }
 
-pub fn update() -> Result<()> {
-    init_logger();
-    subcommand::updater::update()?;
+pub fn update(filter: &Filter, opts: &UpdateOptions) -> Result<()> {
+    init();
+    subcommand::updater::update(filter, opts)?;
     Ok(())
 }
 
-pub fn view() -> Result<()> {
-    init_logger();
-    subcommand::viewer::view()?;
+pub fn install(filter: &Filter) -> Result<()> {
+    init();
+    subcommand::installer::install(filter)?;
     Ok(())
 }
 
-pub fn update_with_confirm() -> Result<()> {
-    init_logger();
-    subcommand::updater::update_after_checking()?;
+pub fn clean(filter: &Filter, opts: &CleanOptions) -> Result<()> {
+    init();
+    subcommand::cleaner::clean(filter, opts)?;
     Ok(())
 }
 
+pub fn du(filter: &Filter) -> Result<()> {
+    init();
+    subcommand::housekeeper::du(filter)?;
+    Ok(())
+}
+
+pub fn gc(filter: &Filter, opts: &GcOptions) -> Result<()> {
+    init();
+    subcommand::housekeeper::gc(filter, opts)?;
+    Ok(())
+}
+
+pub fn log(filter: &Filter, opts: &LogOptions) -> Result<()> {
+    init();
+    subcommand::changelog::log(filter, opts)?;
+    Ok(())
+}
+
+pub fn fix_remotes(filter: &Filter, opts: &FixRemotesOptions) -> Result<()> {
+    init();
+    subcommand::remote_fixer::fix_remotes(filter, opts)?;
+    Ok(())
+}
+
+pub fn stats(filter: &Filter, opts: &StatsOptions) -> Result<()> {
+    init();
+    subcommand::stats::stats(filter, opts)?;
+    Ok(())
+}
+
+pub fn view(filter: &Filter, refresh: bool) -> Result<()> {
+    init();
+    subcommand::viewer::view(filter, refresh)?;
+    Ok(())
+}
+
+pub fn view_output_json(filter: &Filter, refresh: bool) -> Result<()> {
+    init();
+    let j = subcommand::viewer::output_json(filter, refresh)?;
+    println!("{}", j);
+    Ok(())
+}
+
+pub fn update_with_confirm(filter: &Filter, opts: &UpdateOptions) -> Result<()> {
+    init();
+    subcommand::updater::update_after_checking(filter, opts)?;
+    Ok(())
+}
+
+pub fn lock(wait: bool) -> Result<ProcessLock> {
+    lock::acquire(wait)
+}
+
+fn init() {
+    init_logger();
+    if let Err(e) = interrupt::install() {
+        log::warn!("{:?}", e);
+    }
+}
+
 fn init_logger() {
     let mut builder = pretty_env_logger::formatted_builder();
     if let Ok(s) = ::std::env::var("RUST_LOG") {
@@ -0,0 +1,154 @@
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use std::fs::File;
+use std::io::Write;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+lazy_static! {
+    static ref HELD: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+// The flock is what excludes other runs; the kernel drops it when the process exits,
+// so a lock file left behind is never mistaken for a running dotplugs
+#[derive(Debug)]
+pub struct ProcessLock {
+    path: PathBuf,
+    _file: File,
+}
+
+impl Drop for ProcessLock {
+    fn drop(&mut self) {
+        // Removed while still locked; waiters notice the new inode and reopen
+        std::fs::remove_file(&self.path).unwrap_or(());
+        let mut held = HELD.lock().unwrap();
+        if held.as_deref() == Some(self.path.as_path()) {
+            held.take();
+        }
+    }
+}
+
+// For exits that skip destructors, such as Ctrl-C while nothing is running
+pub fn remove_held() {
+    if let Some(path) = HELD.lock().unwrap().take() {
+        std::fs::remove_file(path).unwrap_or(());
+    }
+}
+
+pub fn acquire(wait: bool) -> Result<ProcessLock> {
+    let mut path = crate::config::state_dir()?;
+    std::fs::create_dir_all(&path)?;
+    path.push("dotplugs.pid");
+    acquire_at(&path, wait)
+}
+
+fn acquire_at(path: &Path, wait: bool) -> Result<ProcessLock> {
+    let mut notified = false;
+    loop {
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            // Truncating would wipe the holder's pid before we know we own the lock
+            .truncate(false)
+            .open(path)?;
+        if try_lock(&file)? {
+            if !is_same_file(&file, path) {
+                continue;
+            }
+            // The pid is only for messages
+            file.set_len(0)?;
+            write!(file, "{}", std::process::id())?;
+            *HELD.lock().unwrap() = Some(path.to_path_buf());
+            return Ok(ProcessLock {
+                path: path.to_path_buf(),
+                _file: file,
+            });
+        }
+
+        let pid = std::fs::read_to_string(path)
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "unknown".to_string());
+        if !wait {
+            return Err(anyhow!(
+                "another dotplugs is running (pid {}, lock {:?})",
+                pid,
+                path
+            ));
+        }
+        if !notified {
+            eprintln!("Waiting for another dotplugs to finish (pid {})", pid);
+            notified = true;
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+fn try_lock(file: &File) -> Result<bool> {
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+    let e = std::io::Error::last_os_error();
+    if e.raw_os_error() == Some(libc::EWOULDBLOCK) {
+        return Ok(false);
+    }
+    Err(e.into())
+}
+
+fn is_same_file(file: &File, path: &Path) -> bool {
+    match (file.metadata(), std::fs::metadata(path)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn lock_path() -> PathBuf {
+        PathBuf::from(temp_dir("dotplugs_lock"))
+    }
+
+    #[test]
+    fn acquire_twice_fails() -> Result<()> {
+        let path = lock_path();
+        let lock = acquire_at(&path, false)?;
+        assert!(acquire_at(&path, false).is_err());
+        drop(lock);
+        assert!(!path.exists());
+        acquire_at(&path, false)?;
+        Ok(())
+    }
+
+    #[test]
+    fn acquire_stale_lock_ok() -> Result<()> {
+        let path = lock_path();
+        // Left behind by a run that was killed, so nobody holds its flock
+        std::fs::write(&path, "2147483647")?;
+        let _lock = acquire_at(&path, false)?;
+        assert_eq!(
+            std::fs::read_to_string(&path)?,
+            std::process::id().to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn acquire_while_pid_is_unwritten_fails() -> Result<()> {
+        let path = lock_path();
+        // A holder that has locked the file but not written its pid yet
+        let file = std::fs::File::create(&path)?;
+        assert!(try_lock(&file)?);
+        assert!(acquire_at(&path, false).is_err());
+        drop(file);
+        acquire_at(&path, false)?;
+        Ok(())
+    }
+}
@@ -5,24 +5,106 @@ use dotplugs;
 
 fn main() -> Result<()> {
     let matches = args::load()?;
+    let filter = match matches.subcommand() {
+        (_, Some(sub_m)) => filter(sub_m),
+        _ => dotplugs::Filter::default(),
+    };
+    let _lock = match matches.subcommand() {
+        ("check", Some(sub_m))
+        | ("update", Some(sub_m))
+        | ("install", Some(sub_m))
+        | ("clean", Some(sub_m))
+        | ("gc", Some(sub_m))
+        | ("log", Some(sub_m))
+        | ("fix-remotes", Some(sub_m)) => Some(dotplugs::lock(!sub_m.is_present("no-wait"))?),
+        _ => None,
+    };
     match matches.subcommand() {
         ("check", Some(sub_m)) => {
             if sub_m.is_present("json") {
-                return dotplugs::check_output_json();
+                return dotplugs::check_output_json(&filter);
             }
-            dotplugs::check()?
+            dotplugs::check(&filter)?
         }
         ("update", Some(sub_m)) => {
-            if sub_m.is_present("yes") {
-                dotplugs::update()?
+            let opts = dotplugs::UpdateOptions {
+                follow_default_branch: sub_m.is_present("follow-default-branch"),
+                dry_run: sub_m.is_present("dry-run"),
+                json: sub_m.is_present("json"),
+                interactive: sub_m.is_present("interactive"),
+            };
+            if sub_m.is_present("yes") || opts.dry_run {
+                dotplugs::update(&filter, &opts)?
             } else {
-                dotplugs::update_with_confirm()?
+                dotplugs::update_with_confirm(&filter, &opts)?
             }
         }
-        ("viewer", _) => dotplugs::view()?,
+        ("install", _) => dotplugs::install(&filter)?,
+        ("clean", Some(sub_m)) => {
+            let opts = dotplugs::CleanOptions {
+                yes: sub_m.is_present("yes"),
+                force: sub_m.is_present("force"),
+            };
+            dotplugs::clean(&filter, &opts)?
+        }
+        ("du", _) => dotplugs::du(&filter)?,
+        ("gc", Some(sub_m)) => {
+            let depth = match sub_m.value_of("depth") {
+                Some(d) => Some(d.parse().map_err(|_| anyhow!("invalid depth: {}", d))?),
+                None => None,
+            };
+            let opts = dotplugs::GcOptions {
+                repack: sub_m.is_present("repack"),
+                depth,
+            };
+            dotplugs::gc(&filter, &opts)?
+        }
+        ("log", Some(sub_m)) => {
+            let opts = dotplugs::LogOptions {
+                releases: sub_m.is_present("releases"),
+            };
+            dotplugs::log(&filter, &opts)?
+        }
+        ("fix-remotes", Some(sub_m)) => {
+            let opts = dotplugs::FixRemotesOptions {
+                apply: sub_m.is_present("apply"),
+            };
+            dotplugs::fix_remotes(&filter, &opts)?
+        }
+        ("stats", Some(sub_m)) => {
+            let mut opts = dotplugs::StatsOptions::default();
+            if let Some(values) = sub_m.values_of("window") {
+                opts.windows = values
+                    .map(|w| w.parse().map_err(|_| anyhow!("invalid window: {}", w)))
+                    .collect::<Result<_>>()?;
+            }
+            dotplugs::stats(&filter, &opts)?
+        }
+        ("viewer", Some(sub_m)) => {
+            let refresh = sub_m.is_present("refresh");
+            if sub_m.is_present("json") {
+                return dotplugs::view_output_json(&filter, refresh);
+            }
+            dotplugs::view(&filter, refresh)?
+        }
         _ => {
             return Err(anyhow!("subcommand not found"));
         }
     }
     Ok(())
 }
+
+fn filter(sub_m: &clap::ArgMatches) -> dotplugs::Filter {
+    let values = |name| {
+        sub_m
+            .values_of(name)
+            .map(|v| v.map(|s| s.to_string()).collect())
+            .unwrap_or_default()
+    };
+    dotplugs::Filter {
+        names: values("names"),
+        managers: values("manager"),
+        include: values("include"),
+        exclude: values("exclude"),
+    }
+}
@@ -1,37 +1,210 @@
+mod filter;
+mod lockfile;
+mod remote_url;
 mod tpm;
 mod vim_plug;
 mod zinit;
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use serde::Deserialize;
 use serde::Serialize;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+pub use filter::Filter;
+pub use remote_url::RemoteUrl;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Tracking {
+    #[default]
+    Branch,
+    LatestTag,
+    TagPattern(String),
+}
+
+impl Tracking {
+    // A tag pattern without wildcards names exactly one tag
+    pub fn is_pinned(&self) -> bool {
+        matches!(self, Tracking::TagPattern(p) if !p.contains(&['*', '?', '['][..]))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Manager {
+    VimPlug,
+    Zinit,
+    Tpm,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Repository {
     pub uri: String,
     pub dir: String,
+    #[serde(default)]
+    pub track: Tracking,
+    #[serde(default)]
+    pub branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manager: Option<Manager>,
+    #[serde(skip)]
+    pub remote: Option<RemoteUrl>,
 }
 
 pub type Repositories = Vec<Repository>;
 
 trait CanReposit {
-    fn get_repositories() -> Result<Repositories>;
+    fn get_installed_repositories() -> Result<Repositories>;
+
+    fn get_declared_repositories() -> Result<Option<Repositories>> {
+        Ok(None)
+    }
+
+    fn get_repositories() -> Result<Repositories> {
+        let installed = Self::get_installed_repositories()?;
+        // Declarations only add branch and tracking details; clean is the one that needs them
+        match Self::get_declared_repositories() {
+            Ok(Some(declared)) => Ok(merge_declared(installed, declared)),
+            Ok(None) => Ok(installed),
+            Err(e) => {
+                log::warn!("ignoring plugin declarations: {:#}", e);
+                Ok(installed)
+            }
+        }
+    }
+
+    fn get_orphaned_repositories() -> Result<Repositories> {
+        // An empty declaration is more likely a parse failure than a wish to remove everything
+        let declared = match Self::get_declared_repositories()? {
+            Some(d) if !d.is_empty() => d,
+            _ => return Ok(vec![]),
+        };
+        Ok(Self::get_installed_repositories()?
+            .into_iter()
+            .filter(|r| !declared.iter().any(|d| is_same_dir(&r.dir, &d.dir)))
+            .collect())
+    }
 }
 
-pub fn new() -> Result<Repositories> {
-    let mut repos = vec![];
-    repos.extend(vim_plug::VimPlug::get_repositories()?);
-    repos.extend(zinit::Zinit::get_repositories()?);
-    repos.extend(tpm::Tpm::get_repositories()?);
+pub fn new(filter: &Filter) -> Result<Repositories> {
+    let mut repos = lockfile::Lockfile::get_repositories()?;
+    let mut managed = vec![];
+    managed.extend(with_manager(
+        vim_plug::VimPlug::get_repositories()?,
+        Manager::VimPlug,
+    ));
+    managed.extend(with_manager(
+        zinit::Zinit::get_repositories()?,
+        Manager::Zinit,
+    ));
+    managed.extend(with_manager(tpm::Tpm::get_repositories()?, Manager::Tpm));
+    for repo in managed {
+        if !repos.iter().any(|r| is_same_dir(&r.dir, &repo.dir)) {
+            repos.push(repo);
+        }
+    }
+    for repo in repos.iter_mut() {
+        repo.remote = RemoteUrl::parse(&repo.uri).ok();
+    }
+    let mut repos = filter.apply(repos)?;
+    apply_config(&mut repos)?;
     Ok(repos)
 }
 
+pub fn orphans(filter: &Filter) -> Result<Repositories> {
+    let declared = lockfile::Lockfile::get_repositories()?;
+    let mut orphans = vec![];
+    if filter.wants(Manager::VimPlug) {
+        orphans.extend(with_manager(
+            vim_plug::VimPlug::get_orphaned_repositories()?,
+            Manager::VimPlug,
+        ));
+    }
+    if filter.wants(Manager::Tpm) {
+        orphans.extend(with_manager(
+            tpm::Tpm::get_orphaned_repositories()?,
+            Manager::Tpm,
+        ));
+    }
+    Ok(filter
+        .apply(orphans)?
+        .into_iter()
+        .filter(|r| !declared.iter().any(|d| is_same_dir(&r.dir, &d.dir)))
+        .collect())
+}
+
+// The dotfile (or lockfile) that declares the plugin
+pub fn declaration_file(repo: &Repository) -> Result<Option<std::path::PathBuf>> {
+    let path = match repo.manager {
+        Some(Manager::VimPlug) => Some(vim_plug::VimPlug::get_vimrc()?),
+        Some(Manager::Zinit) => Some(zinit::Zinit::get_zshrc()?),
+        Some(Manager::Tpm) => tpm::Tpm::get_tmux_conf(),
+        None => Some(lockfile::Lockfile::get_lockfile_path()?),
+    };
+    Ok(path.filter(|p| p.exists()))
+}
+
+fn merge_declared(installed: Repositories, declared: Repositories) -> Repositories {
+    let mut repos = installed;
+    for d in declared {
+        match repos.iter_mut().find(|r| is_same_dir(&r.dir, &d.dir)) {
+            Some(r) => {
+                r.branch = d.branch;
+                r.track = d.track;
+            }
+            None => repos.push(d),
+        }
+    }
+    repos
+}
+
+fn with_manager(repos: Repositories, manager: Manager) -> Repositories {
+    repos
+        .into_iter()
+        .map(|r| Repository {
+            manager: Some(manager),
+            ..r
+        })
+        .collect()
+}
+
+fn is_same_dir(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+fn apply_config(repos: &mut Repositories) -> Result<()> {
+    let config = crate::config::get()?;
+    for repo in repos.iter_mut() {
+        if let Some(track) = config.plugin(repo).and_then(|p| p.track.clone()) {
+            repo.track = track;
+        }
+    }
+    Ok(())
+}
+
+impl Manager {
+    pub fn from_name(name: &str) -> Option<Manager> {
+        match name {
+            "vim-plug" | "vim" => Some(Manager::VimPlug),
+            "zinit" | "zsh" => Some(Manager::Zinit),
+            "tpm" | "tmux" => Some(Manager::Tpm),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Manager::VimPlug => "vim-plug",
+            Manager::Zinit => "zinit",
+            Manager::Tpm => "tpm",
+        }
+    }
+}
+
 impl Repository {
-    pub fn get_name_with_owner(&self) -> Result<String> {
-        let mut parts = self.uri.trim_end_matches(".git").rsplit('/');
-        match (parts.next(), parts.next()) {
-            (Some(name), Some(owner)) => Ok(format!("{}/{}", owner, name)),
-            _ => Err(anyhow!("wrong format for the repository name param (we expect something like facebook/graphql)"))
+    pub fn remote_url(&self) -> Result<RemoteUrl> {
+        match &self.remote {
+            Some(r) => Ok(r.clone()),
+            None => RemoteUrl::parse(&self.uri),
         }
     }
 }
@@ -92,6 +265,7 @@ mod git_directory {
                     let repo = Repository {
                         uri: url,
                         dir: path.to_str().ok_or(anyhow!("convert error"))?.to_string(),
+                        ..Default::default()
                     };
                     r.push(repo);
                 }
@@ -144,9 +318,46 @@ mod tests {
         let r = Repository {
             uri: "https://git::@github.com/kana/vim-operator-user.git".to_string(),
             dir: "/home/test/.vim/plugged/vim-operator-user/".to_string(),
+            ..Default::default()
         };
-        let s = r.get_name_with_owner()?;
+        let s = r.remote_url()?.name_with_owner();
         assert_eq!(s, "kana/vim-operator-user");
         Ok(())
     }
+
+    struct Fake;
+
+    impl CanReposit for Fake {
+        fn get_installed_repositories() -> Result<Repositories> {
+            Ok(vec![
+                Repository {
+                    uri: "https://github.com/tpope/vim-surround".to_string(),
+                    dir: "/home/test/.vim/plugged/vim-surround".to_string(),
+                    ..Default::default()
+                },
+                Repository {
+                    uri: "https://github.com/tpope/vim-rails".to_string(),
+                    dir: "/home/test/.vim/plugged/vim-rails".to_string(),
+                    ..Default::default()
+                },
+            ])
+        }
+
+        fn get_declared_repositories() -> Result<Option<Repositories>> {
+            Ok(Some(vec![Repository {
+                uri: "https://github.com/tpope/vim-surround".to_string(),
+                dir: "/home/test/.vim/plugged/vim-surround/".to_string(),
+                ..Default::default()
+            }]))
+        }
+    }
+
+    #[test]
+    fn get_orphaned_repositories_ok() -> Result<()> {
+        let orphans = Fake::get_orphaned_repositories()?;
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].dir, "/home/test/.vim/plugged/vim-rails");
+        assert_eq!(Fake::get_repositories()?.len(), 2);
+        Ok(())
+    }
 }
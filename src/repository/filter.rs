@@ -0,0 +1,145 @@
+use crate::repository::Manager;
+use crate::repository::Repositories;
+use crate::repository::Repository;
+use anyhow::{anyhow, Result};
+use log::warn;
+
+#[derive(Debug, Default, Clone)]
+pub struct Filter {
+    pub names: Vec<String>,
+    pub managers: Vec<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl Filter {
+    pub fn wants(&self, manager: Manager) -> bool {
+        self.managers.is_empty()
+            || self
+                .managers
+                .iter()
+                .any(|m| Manager::from_name(m) == Some(manager))
+    }
+
+    pub fn apply(&self, repos: Repositories) -> Result<Repositories> {
+        let managers = self
+            .managers
+            .iter()
+            .map(|m| Manager::from_name(m).ok_or(anyhow!("unknown plugin manager: {}", m)))
+            .collect::<Result<Vec<_>>>()?;
+        let include = compile(&self.include)?;
+        let exclude = compile(&self.exclude)?;
+
+        let repos: Repositories = repos
+            .into_iter()
+            .filter(|r| self.names.is_empty() || self.names.iter().any(|n| is_named(r, n)))
+            .filter(|r| managers.is_empty() || r.manager.is_some_and(|m| managers.contains(&m)))
+            .filter(|r| include.is_empty() || include.iter().any(|p| p.matches(&full_name(r))))
+            .filter(|r| !exclude.iter().any(|p| p.matches(&full_name(r))))
+            .collect();
+        for n in &self.names {
+            if !repos.iter().any(|r| is_named(r, n)) {
+                warn!("no plugin matches {}", n);
+            }
+        }
+        Ok(repos)
+    }
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<glob::Pattern>> {
+    patterns
+        .iter()
+        .map(|p| glob::Pattern::new(p).map_err(|e| anyhow!("invalid pattern {}: {}", p, e)))
+        .collect()
+}
+
+fn full_name(repo: &Repository) -> String {
+    repo.remote_url()
+        .map(|r| r.name_with_owner())
+        .unwrap_or_else(|_| repo.uri.to_string())
+}
+
+fn is_named(repo: &Repository, name: &str) -> bool {
+    let dir_name = std::path::Path::new(&repo.dir)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    full_name(repo).eq_ignore_ascii_case(name)
+        || dir_name
+            .trim_end_matches(".git")
+            .eq_ignore_ascii_case(name.trim_end_matches(".git"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(uri: &str, dir: &str, manager: Option<Manager>) -> Repository {
+        Repository {
+            uri: uri.to_string(),
+            dir: dir.to_string(),
+            manager,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn apply_ok() -> Result<()> {
+        let repos = vec![
+            repo(
+                "https://github.com/junegunn/fzf.git",
+                "/home/test/.vim/plugged/fzf",
+                Some(Manager::VimPlug),
+            ),
+            repo(
+                "https://github.com/tpope/vim-fugitive.git",
+                "/home/test/.vim/plugged/vim-fugitive/",
+                Some(Manager::VimPlug),
+            ),
+            repo(
+                "https://github.com/neoclide/coc.nvim.git",
+                "/home/test/.vim/plugged/coc.nvim",
+                Some(Manager::VimPlug),
+            ),
+            repo(
+                "https://github.com/tmux-plugins/tpm",
+                "/home/test/.tmux/plugins/tpm",
+                Some(Manager::Tpm),
+            ),
+        ];
+
+        let filter = Filter {
+            names: vec!["fzf".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(filter.apply(repos.clone())?.len(), 1);
+
+        let filter = Filter {
+            managers: vec!["tmux".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.apply(repos.clone())?[0].dir,
+            "/home/test/.tmux/plugins/tpm"
+        );
+
+        let filter = Filter {
+            include: vec!["tpope/*".to_string(), "neoclide/*".to_string()],
+            exclude: vec!["neoclide/*".to_string()],
+            ..Default::default()
+        };
+        let filtered = filter.apply(repos.clone())?;
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(
+            filtered[0].remote_url()?.name_with_owner(),
+            "tpope/vim-fugitive"
+        );
+
+        let filter = Filter {
+            managers: vec!["emacs".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.apply(repos).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use crate::repository::CanReposit;
+use crate::repository::Repositories;
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+pub struct Lockfile;
+
+impl CanReposit for Lockfile {
+    fn get_installed_repositories() -> Result<Repositories> {
+        let path = Lockfile::get_lockfile_path()?;
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        Lockfile::get_repositories_from_path(path)
+    }
+}
+
+impl Lockfile {
+    pub fn get_lockfile_path() -> Result<PathBuf> {
+        let mut path = crate::config::config_dir()?;
+        path.push("dotplugs.lock");
+        log::debug!("lockfile: {:?}", &path);
+        Ok(path)
+    }
+
+    fn get_repositories_from_path<P: AsRef<Path>>(path: P) -> Result<Repositories> {
+        let file = File::open(&path)?;
+        let reader = BufReader::new(file);
+        let repos: Repositories = serde_json::from_reader(reader)
+            .with_context(|| format!("while reading {:?}", path.as_ref()))?;
+        Ok(repos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::Tracking;
+
+    #[test]
+    fn get_repositories_from_path_normal() -> Result<()> {
+        let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let lockfile = format!(
+            "{}/tests/data/dotplugs.lock",
+            project_root.to_str().unwrap()
+        );
+        let repos = Lockfile::get_repositories_from_path(lockfile)?;
+        assert_eq!(repos.len(), 3);
+        assert_eq!(repos[0].track, Tracking::LatestTag);
+        assert_eq!(repos[1].track, Tracking::TagPattern("v1.*".to_string()));
+        assert_eq!(repos[2].track, Tracking::Branch);
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scheme {
+    Https,
+    Http,
+    Ssh,
+    Scp,
+    Git,
+    File,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteUrl {
+    pub scheme: Scheme,
+    pub user: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub namespace: String,
+    pub name: String,
+}
+
+impl RemoteUrl {
+    pub fn parse(url: &str) -> Result<RemoteUrl> {
+        let url = url.trim();
+        let (scheme, rest) = match url.split_once("://") {
+            Some((s, rest)) => (parse_scheme(s)?, rest),
+            None if url.starts_with('/') || url.starts_with('.') => (Scheme::File, url),
+            None if is_scp_like(url) => return parse_scp(url),
+            None => return Err(anyhow!("unsupported remote url: {}", url)),
+        };
+        if scheme == Scheme::File {
+            return from_path(scheme, None, None, None, rest);
+        }
+
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let (user, host_port) = match authority.rsplit_once('@') {
+            // vim-plug writes https://git::@github.com/owner/repo.git
+            Some((userinfo, host)) => (userinfo.split(':').next().filter(|u| !u.is_empty()), host),
+            None => (None, authority),
+        };
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((h, p)) if !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()) => {
+                (h, Some(p.parse()?))
+            }
+            _ => (host_port, None),
+        };
+        if host.is_empty() {
+            return Err(anyhow!("missing host: {}", url));
+        }
+        from_path(
+            scheme,
+            user.map(|u| u.to_string()),
+            Some(host.to_lowercase()),
+            port,
+            path,
+        )
+    }
+
+    pub fn name_with_owner(&self) -> String {
+        format!("{}/{}", self.namespace, self.name)
+    }
+
+    pub fn owner(&self) -> &str {
+        self.namespace.split('/').next().unwrap_or_default()
+    }
+
+    pub fn is_github(&self) -> bool {
+        self.host.as_deref() == Some("github.com")
+    }
+
+    pub fn with_name_with_owner(&self, name_with_owner: &str) -> RemoteUrl {
+        let (namespace, name) = name_with_owner
+            .rsplit_once('/')
+            .unwrap_or((&self.namespace, name_with_owner));
+        RemoteUrl {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+            ..self.clone()
+        }
+    }
+
+    // Renders a url git accepts, keeping the scheme of the original remote
+    pub fn url(&self) -> String {
+        let host = self.host.as_deref().unwrap_or_default();
+        let user = match (self.scheme, &self.user) {
+            (Scheme::Ssh, Some(u)) | (Scheme::Scp, Some(u)) => format!("{}@", u),
+            _ => "".to_string(),
+        };
+        let port = self.port.map(|p| format!(":{}", p)).unwrap_or_default();
+        let scheme = match self.scheme {
+            Scheme::Https => "https",
+            Scheme::Http => "http",
+            Scheme::Ssh => "ssh",
+            Scheme::Git => "git",
+            Scheme::Scp => return format!("{}{}:{}.git", user, host, self.name_with_owner()),
+            Scheme::File => return self.name_with_owner(),
+        };
+        format!(
+            "{}://{}{}{}/{}.git",
+            scheme,
+            user,
+            host,
+            port,
+            self.name_with_owner()
+        )
+    }
+}
+
+impl fmt::Display for RemoteUrl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.host, self.is_github()) {
+            (_, true) | (None, _) => write!(f, "{}", self.name_with_owner()),
+            (Some(host), false) => write!(f, "{}/{}", host, self.name_with_owner()),
+        }
+    }
+}
+
+impl std::str::FromStr for RemoteUrl {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<RemoteUrl> {
+        RemoteUrl::parse(s)
+    }
+}
+
+fn parse_scheme(s: &str) -> Result<Scheme> {
+    match s.to_lowercase().as_str() {
+        "https" => Ok(Scheme::Https),
+        "http" => Ok(Scheme::Http),
+        "ssh" | "git+ssh" | "ssh+git" => Ok(Scheme::Ssh),
+        "git" => Ok(Scheme::Git),
+        "file" => Ok(Scheme::File),
+        _ => Err(anyhow!("unsupported scheme: {}", s)),
+    }
+}
+
+// git treats [user@]host:path as ssh when there is no slash before the first colon
+fn is_scp_like(url: &str) -> bool {
+    match url.find(':') {
+        Some(i) => !url[..i].contains('/') && i > 0,
+        None => false,
+    }
+}
+
+fn parse_scp(url: &str) -> Result<RemoteUrl> {
+    let (authority, path) = url.split_once(':').ok_or(anyhow!("invalid url: {}", url))?;
+    let (user, host) = match authority.rsplit_once('@') {
+        Some((u, h)) => (Some(u.to_string()), h),
+        None => (None, authority),
+    };
+    from_path(Scheme::Scp, user, Some(host.to_lowercase()), None, path)
+}
+
+fn from_path(
+    scheme: Scheme,
+    user: Option<String>,
+    host: Option<String>,
+    port: Option<u16>,
+    path: &str,
+) -> Result<RemoteUrl> {
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let path = if scheme == Scheme::File {
+        path
+    } else {
+        path.trim_start_matches('/')
+    };
+    let (namespace, name) = path
+        .rsplit_once('/')
+        .ok_or(anyhow!("missing owner in remote url path: {}", path))?;
+    if name.is_empty() || (namespace.is_empty() && scheme != Scheme::File) {
+        return Err(anyhow!("invalid remote url path: {}", path));
+    }
+    Ok(RemoteUrl {
+        scheme,
+        user,
+        host,
+        port,
+        namespace: namespace.to_string(),
+        name: name.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ok() -> Result<()> {
+        let cases = vec![
+            (
+                "https://github.com/junegunn/fzf.git",
+                Scheme::Https,
+                Some("github.com"),
+                "junegunn",
+                "fzf",
+            ),
+            (
+                "https://git::@github.com/kana/vim-operator-user.git",
+                Scheme::Https,
+                Some("github.com"),
+                "kana",
+                "vim-operator-user",
+            ),
+            (
+                "git@github.com:neoclide/coc.nvim.git",
+                Scheme::Scp,
+                Some("github.com"),
+                "neoclide",
+                "coc.nvim",
+            ),
+            (
+                "ssh://git@gitlab.com:2222/group/sub/repo.git",
+                Scheme::Ssh,
+                Some("gitlab.com"),
+                "group/sub",
+                "repo",
+            ),
+            (
+                "git://git.sr.ht/~user/plugin",
+                Scheme::Git,
+                Some("git.sr.ht"),
+                "~user",
+                "plugin",
+            ),
+            (
+                "https://GitHub.com/tpope/vim-fugitive/",
+                Scheme::Https,
+                Some("github.com"),
+                "tpope",
+                "vim-fugitive",
+            ),
+            (
+                "file:///srv/git/plugins/local.git",
+                Scheme::File,
+                None,
+                "/srv/git/plugins",
+                "local",
+            ),
+            ("/srv/git/plugin", Scheme::File, None, "/srv/git", "plugin"),
+        ];
+        for (url, scheme, host, namespace, name) in cases {
+            let r = RemoteUrl::parse(url)?;
+            assert_eq!(r.scheme, scheme, "{}", url);
+            assert_eq!(r.host.as_deref(), host, "{}", url);
+            assert_eq!(r.namespace, namespace, "{}", url);
+            assert_eq!(r.name, name, "{}", url);
+        }
+
+        let r = RemoteUrl::parse("ssh://git@gitlab.com:2222/group/sub/repo.git")?;
+        assert_eq!(r.port, Some(2222));
+        assert_eq!(r.user.as_deref(), Some("git"));
+        assert_eq!(r.owner(), "group");
+        assert_eq!(r.to_string(), "gitlab.com/group/sub/repo");
+        let r = RemoteUrl::parse("https://git::@github.com/kana/vim-operator-user.git")?;
+        assert_eq!(r.user.as_deref(), Some("git"));
+        assert_eq!(r.to_string(), "kana/vim-operator-user");
+
+        let r = RemoteUrl::parse("git@github.com:neoclide/coc.nvim.git")?;
+        assert_eq!(
+            r.with_name_with_owner("new-owner/coc.nvim").url(),
+            "git@github.com:new-owner/coc.nvim.git"
+        );
+        let r = RemoteUrl::parse("https://git::@github.com/kana/vim-operator-user.git")?;
+        assert_eq!(
+            r.with_name_with_owner("kana/vim-operator").url(),
+            "https://github.com/kana/vim-operator.git"
+        );
+
+        assert!(RemoteUrl::parse("https://github.com/").is_err());
+        assert!(RemoteUrl::parse("vim-fugitive").is_err());
+        Ok(())
+    }
+}
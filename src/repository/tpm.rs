@@ -1,34 +1,217 @@
 use crate::repository::git_directory::GitDirectory;
 use crate::repository::CanReposit;
 use crate::repository::Repositories;
-use anyhow::Result;
-use std::process::Command;
+use crate::repository::Repository;
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
 
 pub struct Tpm;
 
 impl CanReposit for Tpm {
-    fn get_repositories() -> Result<Repositories> {
-        if !Tpm::is_running_tmux()? {
-            return Ok(vec![]);
-        }
-        let path = match std::env::var("TMUX_PLUGIN_MANAGER_PATH") {
-            Ok(n) => n,
-            Err(_) => return Ok(vec![]),
+    fn get_installed_repositories() -> Result<Repositories> {
+        let path = Tpm::get_plugin_root_dir()?;
+        GitDirectory::get_repositories(path.to_str().ok_or(anyhow!("convert error"))?)
+    }
+
+    fn get_declared_repositories() -> Result<Option<Repositories>> {
+        let conf = match Tpm::get_tmux_conf() {
+            Some(x) => x,
+            None => return Ok(None),
         };
-        GitDirectory::get_repositories(path)
+        log::debug!("tmux conf: {:?}", &conf);
+        let mut repos = vec![];
+        Tpm::read_conf(&conf, &Tpm::get_plugin_root_dir()?, &mut vec![], &mut repos)?;
+        Ok(Some(repos))
     }
 }
 
 impl Tpm {
-    fn is_running_tmux() -> Result<bool> {
-        let cmd = format!(r##"tmux info"##);
-        log::debug!("exists check: {}", cmd);
-        let status = Command::new("sh")
-            .arg("-c")
-            .arg(cmd)
-            .stdout(std::process::Stdio::null())
-            .status()?;
-        log::debug!("process exited with: {}", status);
-        Ok(status.success())
+    fn get_plugin_root_dir() -> Result<PathBuf> {
+        if let Ok(n) = std::env::var("TMUX_PLUGIN_MANAGER_PATH") {
+            return Ok(PathBuf::from(n));
+        }
+        let mut dir = dirs::home_dir().ok_or(anyhow!("Home env not found"))?;
+        dir.push(".tmux/plugins");
+        Ok(dir)
+    }
+
+    pub fn get_tmux_conf() -> Option<PathBuf> {
+        let mut candidates = vec![];
+        if let Some(mut p) = dirs::home_dir() {
+            p.push(".tmux.conf");
+            candidates.push(p);
+        }
+        if let Some(mut p) = dirs::config_dir() {
+            p.push("tmux/tmux.conf");
+            candidates.push(p);
+        }
+        candidates.into_iter().find(|p| p.exists())
+    }
+
+    // Plugins may be declared in files pulled in with source-file. An include that cannot be
+    // resolved is an error, as its plugins would otherwise look orphaned to clean.
+    fn read_conf(
+        path: &Path,
+        root: &Path,
+        seen: &mut Vec<PathBuf>,
+        repos: &mut Repositories,
+    ) -> Result<()> {
+        let path = std::fs::canonicalize(path).with_context(|| format!("{:?}", path))?;
+        if seen.contains(&path) {
+            return Ok(());
+        }
+        seen.push(path.clone());
+        let s = std::fs::read_to_string(&path).with_context(|| format!("{:?}", path))?;
+        repos.extend(Tpm::parse_plugins(&s, root));
+        let base = path.parent().unwrap_or_else(|| Path::new("/"));
+        for (source, quiet) in Tpm::parse_sources(&s) {
+            for p in Tpm::resolve_source(&source, quiet, base)? {
+                Tpm::read_conf(&p, root, seen, repos)?;
+            }
+        }
+        Ok(())
+    }
+
+    // (path, -q) of each source-file line
+    fn parse_sources(conf: &str) -> Vec<(String, bool)> {
+        conf.lines()
+            .map(|l| l.trim())
+            .filter(|l| l.starts_with("source-file ") || l.starts_with("source "))
+            .filter_map(|l| {
+                let mut quiet = false;
+                let path = l
+                    .split_whitespace()
+                    .skip(1)
+                    .find(|w| {
+                        if w.starts_with('-') {
+                            quiet |= w.contains('q');
+                            false
+                        } else {
+                            true
+                        }
+                    })?
+                    .trim_matches(|c| c == '\'' || c == '"');
+                Some((path.to_string(), quiet))
+            })
+            .collect()
+    }
+
+    fn resolve_source(source: &str, quiet: bool, base: &Path) -> Result<Vec<PathBuf>> {
+        let home = dirs::home_dir().ok_or(anyhow!("Home env not found"))?;
+        let home = home.to_str().ok_or(anyhow!("convert error"))?;
+        let expanded = if let Some(rest) = source.strip_prefix("~/") {
+            format!("{}/{}", home, rest)
+        } else {
+            source.replace("$HOME", home).replace("${HOME}", home)
+        };
+        if expanded.contains('$') || expanded.contains("#{") {
+            return Err(anyhow!("cannot resolve tmux source-file {}", source));
+        }
+        let pattern = base.join(&expanded);
+        let pattern = pattern.to_str().ok_or(anyhow!("convert error"))?;
+        let paths: Vec<PathBuf> = glob::glob(pattern)?.filter_map(|p| p.ok()).collect();
+        if paths.is_empty() && !quiet {
+            return Err(anyhow!("tmux source-file {} not found", source));
+        }
+        Ok(paths)
+    }
+
+    fn parse_plugins(conf: &str, root: &Path) -> Repositories {
+        conf.lines()
+            .map(|l| l.trim())
+            .filter(|l| l.starts_with("set ") || l.starts_with("set-option "))
+            .filter_map(|l| {
+                let mut words = l
+                    .split_whitespace()
+                    .skip(1)
+                    .skip_while(|w| w.starts_with('-'));
+                match (words.next(), words.next()) {
+                    (Some("@plugin"), Some(spec)) => {
+                        Some(spec.trim_matches(|c| c == '\'' || c == '"'))
+                    }
+                    _ => None,
+                }
+            })
+            .map(|spec| {
+                let mut parts = spec.splitn(2, '#');
+                let name = parts.next().unwrap_or_default();
+                let branch = parts.next().map(|b| b.to_string());
+                let uri = if name.contains("://") || name.starts_with("git@") {
+                    name.to_string()
+                } else {
+                    format!("https://github.com/{}", name)
+                };
+                let mut dir = root.to_path_buf();
+                dir.push(
+                    name.trim_end_matches(".git")
+                        .rsplit('/')
+                        .next()
+                        .unwrap_or_default(),
+                );
+                Repository {
+                    uri,
+                    dir: dir.to_string_lossy().to_string(),
+                    branch,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_plugins_ok() {
+        let conf = r#"
+            set -g @plugin 'tmux-plugins/tpm'
+            set-option -g @plugin "tmux-plugins/tmux-yank#v2.3.0"
+            set -g @plugin 'git@github.com:user/plugin.git'
+            # set -g @plugin 'tmux-plugins/tmux-sensible'
+            set -g @continuum-restore 'on'
+        "#;
+        let repos = Tpm::parse_plugins(conf, Path::new("/home/test/.tmux/plugins"));
+        assert_eq!(repos.len(), 3);
+        assert_eq!(repos[0].uri, "https://github.com/tmux-plugins/tpm");
+        assert_eq!(repos[0].dir, "/home/test/.tmux/plugins/tpm");
+        assert_eq!(repos[1].branch, Some("v2.3.0".to_string()));
+        assert_eq!(repos[1].dir, "/home/test/.tmux/plugins/tmux-yank");
+        assert_eq!(repos[2].uri, "git@github.com:user/plugin.git");
+        assert_eq!(repos[2].dir, "/home/test/.tmux/plugins/plugin");
+    }
+
+    #[test]
+    fn read_conf_follows_source_file() -> Result<()> {
+        let dir = crate::test_util::temp_dir("dotplugs_tpm");
+        std::fs::create_dir_all(format!("{}/conf.d", dir))?;
+        let conf = PathBuf::from(format!("{}/tmux.conf", dir));
+        std::fs::write(
+            &conf,
+            "set -g @plugin 'tmux-plugins/tpm'\n\
+             source-file -q conf.d/*.conf\n\
+             source -q missing.conf\n",
+        )?;
+        std::fs::write(
+            format!("{}/conf.d/plugins.conf", dir),
+            "set -g @plugin 'tmux-plugins/tmux-yank'\nsource-file '../tmux.conf'\n",
+        )?;
+        let root = Path::new("/home/test/.tmux/plugins");
+        let mut repos = vec![];
+        Tpm::read_conf(&conf, root, &mut vec![], &mut repos)?;
+        let uris: Vec<&str> = repos.iter().map(|r| r.uri.as_str()).collect();
+        assert_eq!(
+            uris,
+            vec![
+                "https://github.com/tmux-plugins/tpm",
+                "https://github.com/tmux-plugins/tmux-yank"
+            ]
+        );
+
+        std::fs::write(&conf, "source-file missing.conf\n")?;
+        assert!(Tpm::read_conf(&conf, root, &mut vec![], &mut vec![]).is_err());
+        std::fs::remove_dir_all(&dir).unwrap_or(());
+        Ok(())
     }
 }
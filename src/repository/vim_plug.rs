@@ -1,21 +1,71 @@
 use crate::repository::git_directory::GitDirectory;
 use crate::repository::CanReposit;
 use crate::repository::Repositories;
+use crate::repository::{Repository, Tracking};
 use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 pub struct VimPlug;
 
+// g:plug_home and g:plugs as one nvim run reports them
+#[derive(Debug, Deserialize)]
+struct Plugs {
+    home: String,
+    plugs: Vec<PlugSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlugSpec {
+    uri: String,
+    dir: String,
+    #[serde(default)]
+    branch: String,
+    #[serde(default)]
+    tag: String,
+}
+
+lazy_static! {
+    static ref PLUGS: std::result::Result<Option<Plugs>, String> =
+        VimPlug::query_plugs().map_err(|e| format!("{:?}", e));
+}
+
 impl CanReposit for VimPlug {
-    fn get_repositories() -> Result<Repositories> {
-        let path = VimPlug::get_plugin_root_dir()?;
-        GitDirectory::get_repositories(path)
+    fn get_installed_repositories() -> Result<Repositories> {
+        match VimPlug::get_plugin_root_dir() {
+            Ok(path) => GitDirectory::get_repositories(path),
+            Err(e) => {
+                log::debug!("{:?}", e);
+                Ok(vec![])
+            }
+        }
+    }
+
+    fn get_declared_repositories() -> Result<Option<Repositories>> {
+        match PLUGS.as_ref() {
+            Ok(plugs) => Ok(plugs.as_ref().map(|p| VimPlug::to_repositories(&p.plugs))),
+            Err(e) => {
+                log::debug!("vim-plug declared plugins are not available: {}", e);
+                Ok(None)
+            }
+        }
     }
 }
 
 impl VimPlug {
     fn get_plugin_root_dir() -> Result<String> {
-        let mut dir = dirs::home_dir().ok_or(anyhow!("Home env not found"))?;
-        dir.push(".vim/plugged");
+        let dir = match PLUGS.as_ref() {
+            Ok(Some(plugs)) if !plugs.home.is_empty() => PathBuf::from(&plugs.home),
+            _ => {
+                let mut dir = dirs::home_dir().ok_or(anyhow!("Home env not found"))?;
+                dir.push(".vim/plugged");
+                dir
+            }
+        };
         log::debug!("vim-plug dir: {:?}", &dir);
         if !&dir.exists() {
             return Err(anyhow!("vim-plug dir not found {:?}", &dir));
@@ -25,4 +75,124 @@ impl VimPlug {
             .into_string()
             .map_err(|x| anyhow!("Home env not found {:?}", x))?)
     }
+
+    // $MYVIMRC first, then vim's and nvim's own locations; ~/.vimrc when none exists
+    pub fn get_vimrc() -> Result<PathBuf> {
+        let home = dirs::home_dir().ok_or(anyhow!("Home env not found"))?;
+        let mut candidates = vec![];
+        if let Some(p) = std::env::var_os("MYVIMRC").filter(|p| !p.is_empty()) {
+            candidates.push(PathBuf::from(p));
+        }
+        candidates.push(home.join(".vimrc"));
+        candidates.push(home.join(".vim/vimrc"));
+        if let Some(dir) = dirs::config_dir() {
+            candidates.push(dir.join("nvim/init.vim"));
+            candidates.push(dir.join("nvim/init.lua"));
+        }
+        Ok(candidates
+            .into_iter()
+            .find(|p| p.exists())
+            .unwrap_or_else(|| home.join(".vimrc")))
+    }
+
+    // Runs nvim once per process; None when the vimrc does not load vim-plug
+    fn query_plugs() -> Result<Option<Plugs>> {
+        let vimrc = VimPlug::get_vimrc()?;
+        if !vimrc.exists() {
+            return Ok(None);
+        }
+        let path = VimPlug::output_plugins_file(&vimrc)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let plugs = VimPlug::get_plugs_from_path(&path);
+        std::fs::remove_file(&path).unwrap_or(());
+        Ok(Some(plugs?))
+    }
+
+    fn output_plugins_file<P: AsRef<Path>>(vimrc: P) -> Result<PathBuf> {
+        let mut path = std::env::temp_dir();
+        path.push(format!("dotplugs_vim_plug_{}.json", std::process::id()));
+        let cmd = format!(
+            r##"if !exists(':PlugInstall') | cq | endif | call writefile([json_encode({{'home': get(g:, 'plug_home', ''), 'plugs': map(filter(values(g:plugs), {{_, v -> has_key(v, 'uri')}}), {{_, v -> {{'uri': v.uri, 'dir': v.dir, 'branch': get(v, 'branch', ''), 'tag': get(v, 'tag', '')}}}})}})], '{}') | qall"##,
+            path.to_str().ok_or(anyhow!("convert error"))?
+        );
+        log::debug!("output vim-plug list: {}", cmd);
+        // Without a stdin to fall back on, an error in the command chain cannot leave nvim waiting
+        let status = Command::new("nvim")
+            .arg("-es")
+            .arg("-u")
+            .arg(vimrc.as_ref())
+            .arg(format!("+{}", cmd))
+            .stdin(Stdio::null())
+            .status()?;
+        log::debug!("process exited with: {}", status);
+        Ok(path)
+    }
+
+    fn get_plugs_from_path<P: AsRef<Path>>(path: P) -> Result<Plugs> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    fn to_repositories(plugs: &[PlugSpec]) -> Repositories {
+        plugs
+            .iter()
+            .map(|p| Repository {
+                uri: p.uri.to_string(),
+                dir: p.dir.to_string(),
+                branch: Some(p.branch.to_string()).filter(|b| !b.is_empty()),
+                // vim-plug's tag accepts the same wildcards as a tag pattern
+                track: match p.tag.as_str() {
+                    "" => Tracking::Branch,
+                    "*" => Tracking::LatestTag,
+                    t => Tracking::TagPattern(t.to_string()),
+                },
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init() {
+        let _ = pretty_env_logger::formatted_builder()
+            .is_test(true)
+            .parse_filters("DEBUG")
+            .try_init();
+    }
+
+    #[test]
+    fn get_plugs_from_path_normal() -> Result<()> {
+        init();
+        let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let plug_list = format!(
+            "{}/tests/data/vim_plug.json",
+            project_root.to_str().unwrap()
+        );
+
+        let plugs = VimPlug::get_plugs_from_path(plug_list)?;
+        assert_eq!(plugs.home, "/home/osft/.vim/plugged");
+        let repos = VimPlug::to_repositories(&plugs.plugs);
+        assert!(repos.iter().all(|r| r.branch.is_none()));
+        assert_eq!(repos[0].track, Tracking::TagPattern("v1.*".to_string()));
+        assert_eq!(repos[1].track, Tracking::LatestTag);
+        assert_eq!(repos[2].track, Tracking::Branch);
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn output_file_normal() -> Result<()> {
+        init();
+        let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let vimrc = format!("{}/tests/data/vimrc", project_root.to_str().unwrap());
+        let path = VimPlug::output_plugins_file(vimrc)?;
+        assert!(path.exists());
+        Ok(())
+    }
 }
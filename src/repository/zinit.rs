@@ -2,18 +2,25 @@ use crate::repository::git_directory::GitDirectory;
 use crate::repository::CanReposit;
 use crate::repository::Repositories;
 use anyhow::{anyhow, Result};
+use std::path::PathBuf;
 use std::process::Command;
 
 pub struct Zinit;
 
 impl CanReposit for Zinit {
-    fn get_repositories() -> Result<Repositories> {
+    fn get_installed_repositories() -> Result<Repositories> {
         let path = Zinit::get_plugin_root_dir()?;
         GitDirectory::get_repositories(path)
     }
 }
 
 impl Zinit {
+    pub fn get_zshrc() -> Result<PathBuf> {
+        let mut zshrc = dirs::home_dir().ok_or(anyhow!("Home env not found"))?;
+        zshrc.push(".zshrc");
+        Ok(zshrc)
+    }
+
     fn get_plugin_root_dir() -> Result<String> {
         let cmd = format!(
             r##"source ~/.zshrc && zinit zstatus | grep 'Plugin directory' | cut -d' ' -f3 | tr -d '\n' | sed 's/\x1b\[[0-9;]*m//g'"##
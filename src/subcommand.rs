@@ -1,3 +1,9 @@
+pub mod changelog;
 pub mod checker;
+pub mod cleaner;
+pub mod housekeeper;
+pub mod installer;
+pub mod remote_fixer;
+pub mod stats;
 pub mod updater;
 pub mod viewer;
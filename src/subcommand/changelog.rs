@@ -0,0 +1,100 @@
+use crate::git::{GitStatus, UpdatePlan};
+use crate::hosting::releases::{self, Range, ReleaseNote, Releases};
+use crate::repository::Filter;
+use anyhow::Result;
+use colored::Colorize;
+use log::warn;
+
+#[derive(Debug, Default)]
+pub struct LogOptions {
+    pub releases: bool,
+}
+
+pub fn log(filter: &Filter, opts: &LogOptions) -> Result<()> {
+    if opts.releases {
+        crate::hosting::github::token::allow_credential_helper();
+    }
+    let repos = crate::repository::new(filter)?;
+    let statuses = crate::git::get_status(&repos)?;
+    crate::interrupt::check()?;
+    let plans: Vec<UpdatePlan> = crate::git::plan_updates(&statuses, false)
+        .into_iter()
+        .filter(|p| p.commits > 0)
+        .collect();
+    if plans.is_empty() {
+        eprintln!("{}", "Nothing to update".bold());
+        return Ok(());
+    }
+    for p in &plans {
+        println!(
+            "{} {}..{} ({} commits){}",
+            crate::display::name(&p.uri).bold(),
+            &p.old_commit[..7],
+            &p.new_commit[..7],
+            p.commits,
+            p.target
+                .as_ref()
+                .map(|t| format!(" → {}", t))
+                .unwrap_or_default()
+        );
+        if opts.releases {
+            let status = statuses.iter().find(|s| s.dir == p.dir);
+            match status.map(|s| release_notes(s, p)).transpose() {
+                Ok(Some(releases)) if !releases.notes.is_empty() => {
+                    display_release_notes(&releases.notes);
+                    if releases.truncated {
+                        println!(
+                            "    {}",
+                            format!(
+                                "only the latest {} releases were fetched, older ones are not shown",
+                                releases::RELEASE_COUNT
+                            )
+                            .dimmed()
+                        );
+                    }
+                    continue;
+                }
+                Ok(_) => println!("    {}", "no release notes, showing commits".dimmed()),
+                Err(e) => warn!("{}: {}", p.uri, e),
+            }
+        }
+        for s in crate::git::get_commit_subjects(&p.dir, &p.old_commit, &p.new_commit)? {
+            println!("    {}", s);
+        }
+    }
+    Ok(())
+}
+
+fn release_notes(status: &GitStatus, plan: &UpdatePlan) -> Result<Releases> {
+    let remote = status.repository().remote_url()?;
+    let range = Range {
+        current_tag: crate::git::get_head_tag(&plan.dir)?,
+        since: crate::git::get_commit_time(&plan.dir, &plan.old_commit)?,
+        tags: Some(crate::git::get_tags_between(
+            &plan.dir,
+            &plan.old_commit,
+            &plan.new_commit,
+        )?),
+    };
+    releases::fetch(&remote, &range)
+}
+
+fn display_release_notes(notes: &[ReleaseNote]) {
+    for n in notes {
+        println!(
+            "    {}{}{}",
+            n.tag_name.green(),
+            n.name
+                .as_ref()
+                .filter(|name| **name != n.tag_name)
+                .map(|name| format!(" {}", name))
+                .unwrap_or_default(),
+            n.published_at
+                .map(|d| format!(" ({})", d.format("%Y-%m-%d")))
+                .unwrap_or_default()
+        );
+        for line in releases::plain_text(&n.body).lines() {
+            println!("        {}", line);
+        }
+    }
+}
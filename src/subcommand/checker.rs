@@ -1,15 +1,42 @@
+use crate::git::GitStatus;
+use crate::repository::{Filter, Repository};
 use anyhow::Result;
+use log::warn;
 
-pub fn check() -> Result<()> {
-    let repos = crate::repository::new()?;
-    let statuses = crate::git::get_status(&repos)?;
+pub fn check(filter: &Filter) -> Result<()> {
+    let repos = crate::repository::new(filter)?;
+    let mut statuses = crate::git::get_status(&repos)?;
+    // What was checked before Ctrl-C is still worth showing, without the hosting lookup
+    if crate::interrupt::is_interrupted() {
+        crate::display::display(&statuses);
+        return crate::interrupt::check();
+    }
+    add_attention(&repos, &mut statuses)?;
     crate::display::display(&statuses);
+    crate::display::display_attention(&statuses);
     Ok(())
 }
 
-pub fn output_json() -> Result<String> {
-    let repos = crate::repository::new()?;
-    let statuses = crate::git::get_status(&repos)?;
+pub fn output_json(filter: &Filter) -> Result<String> {
+    let repos = crate::repository::new(filter)?;
+    let mut statuses = crate::git::get_status(&repos)?;
+    crate::interrupt::check()?;
+    add_attention(&repos, &mut statuses)?;
     let j = serde_json::to_string(&statuses)?;
     Ok(j)
 }
+
+// Hosting metadata is optional for check, so a failed lookup only warns
+fn add_attention(repos: &[Repository], statuses: &mut [GitStatus]) -> Result<()> {
+    let config = crate::config::get()?;
+    match crate::hosting::create_info(repos, false) {
+        Ok(lookup) => crate::hosting::attention::annotate(
+            statuses,
+            &lookup,
+            config.stale_after(),
+            chrono::Utc::now().naive_utc(),
+        ),
+        Err(e) => warn!("could not fetch hosting metadata: {}", e),
+    }
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use crate::repository::Filter;
+use anyhow::Result;
+use colored::Colorize;
+use log::{debug, warn};
+use prettytable::{format, row, Table};
+
+#[derive(Debug, Default)]
+pub struct CleanOptions {
+    pub yes: bool,
+    pub force: bool,
+}
+
+struct Orphan {
+    dir: String,
+    size: u64,
+    last_commit: String,
+    dirty: bool,
+}
+
+impl Orphan {
+    fn new(dir: String) -> Orphan {
+        // A directory git cannot read (not a repository, corrupt, no permission) counts as dirty
+        let dirty = match crate::git::has_uncommitted_changes(&dir) {
+            Ok(d) => d,
+            Err(e) => {
+                debug!("{}: {}", dir, e);
+                true
+            }
+        };
+        Orphan {
+            size: crate::disk::get_dir_size(&dir),
+            last_commit: crate::git::get_last_commit_time(&dir)
+                .map(|t| t.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|_| "-".to_string()),
+            dirty,
+            dir,
+        }
+    }
+}
+
+pub fn clean(filter: &Filter, opts: &CleanOptions) -> Result<()> {
+    let orphans: Vec<Orphan> = crate::repository::orphans(filter)?
+        .into_iter()
+        .map(|r| Orphan::new(r.dir))
+        .collect();
+    if orphans.is_empty() {
+        eprintln!("{}", "No orphaned plugin directories".bold());
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_CLEAN);
+    for o in &orphans {
+        let dirty = if o.dirty {
+            "modified".red()
+        } else {
+            "".normal()
+        };
+        table.add_row(row![
+            o.dir,
+            r->crate::disk::format_size(o.size),
+            o.last_commit,
+            dirty
+        ]);
+    }
+    table.printstd();
+
+    let total: u64 = orphans.iter().map(|o| o.size).sum();
+    let prompt = format!(
+        "Remove {} directories ({})? [y/N] ",
+        orphans.len(),
+        crate::disk::format_size(total)
+    );
+    if !opts.yes && !is_confirmed_by_user(&prompt)? {
+        println!("Canceled");
+        return Ok(());
+    }
+
+    remove_orphans(&orphans, opts.force)
+}
+
+fn remove_orphans(orphans: &[Orphan], force: bool) -> Result<()> {
+    for o in orphans {
+        if o.dirty && !force {
+            warn!(
+                "{} has uncommitted changes or is not a git repository, skipped (use --force)",
+                o.dir
+            );
+            continue;
+        }
+        std::fs::remove_dir_all(&o.dir)?;
+        eprintln!("Remove: {}", o.dir);
+    }
+    Ok(())
+}
+
+fn is_confirmed_by_user(prompt: &str) -> Result<bool> {
+    eprint!("{}", prompt.bold());
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim_end_matches(&['\r', '\n'][..]);
+    Ok(answer == "Y" || answer == "y")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{git, temp_dir};
+
+    #[test]
+    fn remove_orphans_keeps_dirty_and_non_git_dirs() -> Result<()> {
+        let root = temp_dir("dotplugs_clean");
+        let (plain, clean) = (format!("{}/plain", root), format!("{}/clean", root));
+        std::fs::create_dir_all(&plain)?;
+        std::fs::write(format!("{}/notes.txt", plain), "keep")?;
+        std::fs::create_dir_all(&clean)?;
+        git(&["init"], &clean)?;
+
+        let orphans = vec![
+            Orphan::new(plain.to_string()),
+            Orphan::new(clean.to_string()),
+        ];
+        remove_orphans(&orphans, false)?;
+        assert!(std::path::Path::new(&plain).exists());
+        assert!(!std::path::Path::new(&clean).exists());
+        remove_orphans(&orphans[..1], true)?;
+        assert!(!std::path::Path::new(&plain).exists());
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use crate::disk::format_size;
+use crate::git;
+use crate::repository::Filter;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use prettytable::{format, row, Table};
+use std::path::Path;
+
+#[derive(Debug, Default)]
+pub struct GcOptions {
+    pub repack: bool,
+    pub depth: Option<u32>,
+}
+
+pub fn du(filter: &Filter) -> Result<()> {
+    let repos: Vec<_> = crate::repository::new(filter)?
+        .into_iter()
+        .filter(|r| Path::new(&r.dir).exists())
+        .collect();
+    let mut usages = git::get_disk_usages(&repos)?;
+    usages.sort_by_key(|u| std::cmp::Reverse(u.worktree_size + u.git_size));
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_CLEAN);
+    table.set_titles(row![
+        b->"uri",
+        b->"worktree",
+        b->".git",
+        b->"loose",
+        b->"packed",
+        b->"shallow"
+    ]);
+    for u in &usages {
+        table.add_row(row![
+            u.uri,
+            r->format_size(u.worktree_size),
+            r->format_size(u.git_size),
+            r->u.loose_objects,
+            r->u.packed_objects,
+            if u.shallow { "yes" } else { "" }
+        ]);
+    }
+    table.printstd();
+
+    let worktree: u64 = usages.iter().map(|u| u.worktree_size).sum();
+    let git_size: u64 = usages.iter().map(|u| u.git_size).sum();
+    println!(
+        "{}",
+        format!(
+            "Total: {} (worktree {}, .git {})",
+            format_size(worktree + git_size),
+            format_size(worktree),
+            format_size(git_size)
+        )
+        .bold()
+    );
+    Ok(())
+}
+
+pub fn gc(filter: &Filter, opts: &GcOptions) -> Result<()> {
+    let mode = match (opts.repack, opts.depth) {
+        (_, Some(0)) => return Err(anyhow!("depth must be greater than 0")),
+        (_, Some(depth)) => git::GcMode::Shallow(depth),
+        (true, None) => git::GcMode::Repack,
+        (false, None) => git::GcMode::Gc,
+    };
+    let repos: Vec<_> = crate::repository::new(filter)?
+        .into_iter()
+        .filter(|r| Path::new(&r.dir).exists())
+        .collect();
+    let results = git::collect_garbage(&repos, mode)?;
+
+    let mut failures = 0;
+    for r in &results {
+        match &r.error {
+            Some(e) => {
+                failures += 1;
+                println!("{} {} {}", r.uri, "Failed".red(), e);
+            }
+            None => println!(
+                "{} {} → {}",
+                r.uri,
+                format_size(r.before),
+                format_size(r.after)
+            ),
+        }
+    }
+    let before: u64 = results.iter().map(|r| r.before).sum();
+    let after: u64 = results.iter().map(|r| r.after).sum();
+    eprintln!(
+        "{}",
+        format!(
+            "Reclaimed {} ({} → {})",
+            format_size(before.saturating_sub(after)),
+            format_size(before),
+            format_size(after)
+        )
+        .bold()
+    );
+    if failures > 0 {
+        return Err(anyhow!("{} plugins could not be cleaned up", failures));
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use crate::git;
+use crate::repository::Filter;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use std::path::Path;
+
+pub fn install(filter: &Filter) -> Result<()> {
+    let repos = crate::repository::new(filter)?;
+    let missing: Vec<_> = repos
+        .into_iter()
+        .filter(|r| !Path::new(&r.dir).exists())
+        .collect();
+    if missing.is_empty() {
+        eprintln!("{}", "All plugins are installed".bold());
+        return Ok(());
+    }
+
+    let failures = git::install(&missing)?;
+    if !failures.is_empty() {
+        let state = if crate::interrupt::is_interrupted() {
+            "Not installed (interrupted)"
+        } else {
+            "Failed"
+        };
+        for f in &failures {
+            println!("{} {}", f.uri, state.red());
+        }
+        return Err(anyhow!("{} plugins could not be installed", failures.len()));
+    }
+    eprintln!("{}", "Install successful".bold());
+    Ok(())
+}
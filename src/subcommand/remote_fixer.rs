@@ -0,0 +1,191 @@
+use crate::hosting::attention::{self, Attention};
+use crate::hosting::cache::{self, Cache};
+use crate::repository::Filter;
+use anyhow::Result;
+use colored::Colorize;
+use log::warn;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+#[derive(Debug, Default)]
+pub struct FixRemotesOptions {
+    pub apply: bool,
+}
+
+pub fn fix_remotes(filter: &Filter, opts: &FixRemotesOptions) -> Result<()> {
+    crate::hosting::github::token::allow_credential_helper();
+    let repos = crate::repository::new(filter)?;
+    let lookup = crate::hosting::create_info(&repos, false)?;
+    let cache = Cache::load(&cache::path()?)?;
+    let mut renames: Vec<(PathBuf, String, String)> = vec![];
+    let mut moved = 0;
+    for repo in &repos {
+        let remote = match repo.remote_url() {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        let (olds, new) = match attention::moved(&lookup, &remote) {
+            Some(Attention::Moved {
+                name_with_owner, ..
+            }) => {
+                moved += 1;
+                match crate::git::set_remote_name_with_owner(&repo.dir, &name_with_owner) {
+                    Ok(Some(url)) => println!(
+                        "{} {} → {}",
+                        crate::display::name(&repo.uri),
+                        "Moved".yellow(),
+                        url
+                    ),
+                    Ok(None) => println!(
+                        "{} {} → {} (remote already updated)",
+                        crate::display::name(&repo.uri),
+                        "Moved".yellow(),
+                        name_with_owner
+                    ),
+                    Err(e) => warn!("{}: {}", repo.dir, e),
+                }
+                (vec![remote.name_with_owner()], name_with_owner)
+            }
+            // An earlier run already rewrote the remote, but the dotfiles may still use the old name
+            _ => (cache.previous_names(&remote), remote.name_with_owner()),
+        };
+        if olds.is_empty() {
+            continue;
+        }
+        let path = match crate::repository::declaration_file(repo)? {
+            Some(p) => p,
+            None => {
+                if olds.contains(&remote.name_with_owner()) {
+                    println!("    update its declaration to {}", new);
+                }
+                continue;
+            }
+        };
+        let content = std::fs::read_to_string(&path)?;
+        for old in olds {
+            let mut found = false;
+            for (i, line) in content.lines().enumerate() {
+                if let Some(rewritten) = rewrite_line(line, &old, &new) {
+                    if !found && old != remote.name_with_owner() {
+                        moved += 1;
+                        println!(
+                            "{} {} → {} (remote already updated)",
+                            crate::display::name(&repo.uri),
+                            "Moved".yellow(),
+                            new
+                        );
+                    }
+                    found = true;
+                    println!("    {}:{}", path.display(), i + 1);
+                    println!("    {}", format!("- {}", line.trim()).red());
+                    println!("    {}", format!("+ {}", rewritten.trim()).green());
+                }
+            }
+            if found {
+                renames.push((path.clone(), old, new.clone()));
+            } else if old == remote.name_with_owner() {
+                println!("    {} does not mention {}", path.display(), old);
+            }
+        }
+    }
+    if moved == 0 {
+        eprintln!("{}", "No moved plugins".bold());
+        return Ok(());
+    }
+    if renames.is_empty() {
+        return Ok(());
+    }
+    if !opts.apply {
+        eprintln!("{}", "Dotfiles were not changed (use --apply)".bold());
+        return Ok(());
+    }
+    // Each file is rewritten once with all of its renames
+    let paths: BTreeSet<&PathBuf> = renames.iter().map(|(p, _, _)| p).collect();
+    for path in paths {
+        let content = std::fs::read_to_string(path)?;
+        let rewritten: String = content
+            .split_inclusive('\n')
+            .map(|line| {
+                renames
+                    .iter()
+                    .filter(|(p, _, _)| p == path)
+                    .fold(line.to_string(), |l, (_, old, new)| {
+                        rewrite_line(&l, old, new).unwrap_or(l)
+                    })
+            })
+            .collect();
+        let tmp = path.with_extension("dotplugs.tmp");
+        std::fs::write(&tmp, rewritten)?;
+        std::fs::rename(&tmp, path)?;
+        eprintln!("Rewrite: {}", path.display());
+    }
+    Ok(())
+}
+
+// Replaces owner/name where it stands alone: 'owner/name', owner/name.git or the path of a url
+fn rewrite_line(line: &str, old: &str, new: &str) -> Option<String> {
+    let lower = line.to_ascii_lowercase();
+    let old_lower = old.to_ascii_lowercase();
+    let mut out = String::new();
+    let mut last = 0;
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(&old_lower) {
+        let start = from + pos;
+        let end = start + old.len();
+        from = end;
+        let before = &line[..start];
+        let stands_alone = match before.chars().last() {
+            // only a host may come before the owner, never another path segment
+            Some('/') => before
+                .trim_end_matches('/')
+                .rsplit(['/', '@'])
+                .next()
+                .is_some_and(|s| s.is_empty() || s.contains('.') || s.contains(':')),
+            Some(c) => !is_name_char(c),
+            None => true,
+        };
+        let rest = &line[end..];
+        let rest = rest.strip_prefix(".git").unwrap_or(rest);
+        if !stands_alone || rest.chars().next().is_some_and(is_name_char) {
+            continue;
+        }
+        out.push_str(&line[last..start]);
+        out.push_str(new);
+        last = end;
+    }
+    if last == 0 {
+        return None;
+    }
+    out.push_str(&line[last..]);
+    Some(out)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrite_line_ok() {
+        let rewrite = |line| rewrite_line(line, "owner/old", "newowner/new");
+        assert_eq!(
+            rewrite("Plug 'Owner/old', { 'do': 'make' }").as_deref(),
+            Some("Plug 'newowner/new', { 'do': 'make' }")
+        );
+        assert_eq!(
+            rewrite("set -g @plugin 'git@github.com:owner/old.git'").as_deref(),
+            Some("set -g @plugin 'git@github.com:newowner/new.git'")
+        );
+        assert_eq!(
+            rewrite(r#"{"uri": "https://github.com/owner/old"}"#).as_deref(),
+            Some(r#"{"uri": "https://github.com/newowner/new"}"#)
+        );
+        assert_eq!(rewrite("zinit light owner/old-extra"), None);
+        assert_eq!(rewrite("Plug 'owner/old.vim'"), None);
+        assert_eq!(rewrite("Plug 'someowner/old'"), None);
+        assert_eq!(rewrite("Plug 'https://gitlab.com/group/owner/old'"), None);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::hosting::history::{self, History, Reading};
+use crate::repository::Filter;
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDateTime, Utc};
+use colored::Colorize;
+use prettytable::{format, Cell, Row, Table};
+
+const TOP: usize = 5;
+
+#[derive(Debug)]
+pub struct StatsOptions {
+    pub windows: Vec<i64>,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        StatsOptions {
+            windows: vec![7, 30, 90],
+        }
+    }
+}
+
+pub fn stats(filter: &Filter, opts: &StatsOptions) -> Result<()> {
+    crate::hosting::github::token::allow_credential_helper();
+    if opts.windows.is_empty() || opts.windows.iter().any(|w| *w <= 0) {
+        return Err(anyhow!("windows must be positive numbers of days"));
+    }
+    let repos = crate::repository::new(filter)?;
+    // Also records a fresh reading for anything whose cached metadata expired
+    let lookup = crate::hosting::create_info(&repos, false)?;
+    let history = History::load(&history::path()?)?;
+    let now = Utc::now().naive_utc();
+
+    let mut plugins: Vec<(&str, &[Reading])> = vec![];
+    for r in &repos {
+        let remote = match r.remote_url() {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        if let (Some(host), Some(info)) = (remote.host.as_deref(), lookup.get(&remote)) {
+            let readings = history.get(host, &info.name_with_owner);
+            if !readings.is_empty() && !plugins.iter().any(|(n, _)| *n == info.requested) {
+                plugins.push((&info.requested, readings));
+            }
+        }
+    }
+    if plugins.is_empty() {
+        eprintln!("{}", "No history recorded yet".bold());
+        return Ok(());
+    }
+    plugins.sort_by_key(|(_, readings)| std::cmp::Reverse(readings.last().map(|r| r.stars)));
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_CLEAN);
+    let mut titles = vec!["plugin".to_string(), "stars".to_string()];
+    titles.extend(opts.windows.iter().map(|w| format!("{}d", w)));
+    titles.extend(vec!["issues".to_string(), "pushed".to_string()]);
+    table.set_titles(Row::new(
+        titles
+            .iter()
+            .map(|t| Cell::new(t).style_spec("b"))
+            .collect(),
+    ));
+    for (name, readings) in &plugins {
+        let latest = readings.last().unwrap();
+        let mut cells = vec![
+            Cell::new(name),
+            Cell::new(&latest.stars.to_string()).style_spec("r"),
+        ];
+        for w in &opts.windows {
+            let d = history::delta(readings, since(now, *w), |r| r.stars);
+            cells.push(Cell::new(&format_delta(d)).style_spec("r"));
+        }
+        cells.push(Cell::new(&latest.open_issues.to_string()).style_spec("r"));
+        cells.push(Cell::new(
+            &latest
+                .pushed_at
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+        ));
+        table.add_row(Row::new(cells));
+    }
+    table.printstd();
+
+    // Rankings use the longest window, or as much history as there is while it is shorter
+    let window = *opts.windows.iter().max().unwrap();
+    let mut deltas: Vec<(&str, i64)> = plugins
+        .iter()
+        .filter_map(|(name, readings)| {
+            history::delta_or_earliest(readings, since(now, window), |r| r.stars)
+                .map(|d| (*name, d))
+        })
+        .collect();
+    deltas.sort_by_key(|(_, d)| std::cmp::Reverse(*d));
+    display_ranking(
+        &format!("Fastest growing (up to {}d)", window),
+        deltas.iter().filter(|(_, d)| *d > 0).take(TOP),
+    );
+    display_ranking(
+        &format!("Most declining (up to {}d)", window),
+        deltas.iter().rev().filter(|(_, d)| *d < 0).take(TOP),
+    );
+    Ok(())
+}
+
+fn since(now: NaiveDateTime, days: i64) -> NaiveDateTime {
+    now - Duration::days(days)
+}
+
+fn format_delta(d: Option<i64>) -> String {
+    match d {
+        Some(d) if d > 0 => format!("+{}", d),
+        Some(d) => d.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+fn display_ranking<'a, I>(title: &str, items: I)
+where
+    I: Iterator<Item = &'a (&'a str, i64)>,
+{
+    let items: Vec<_> = items.collect();
+    if items.is_empty() {
+        return;
+    }
+    println!("{}", title.bold());
+    for (name, d) in items {
+        println!("    {} {}", name, format_delta(Some(*d)));
+    }
+}
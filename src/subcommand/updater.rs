@@ -1,38 +1,274 @@
+mod picker;
+
 use crate::git;
-use crate::git::UpdateStatus;
-use anyhow::Result;
+use crate::git::GitStatus;
+use crate::git::UpdatePlan;
+use crate::git::UpdateResult;
+use crate::hook;
+use crate::hook::HookOutput;
+use crate::interrupt;
+use crate::repository::Filter;
+use crate::repository::Repository;
+use anyhow::{anyhow, Result};
 use colored::Colorize;
+use serde::Serialize;
+
+#[derive(Debug, Default)]
+pub struct UpdateOptions {
+    pub follow_default_branch: bool,
+    pub dry_run: bool,
+    pub json: bool,
+    pub interactive: bool,
+}
 
-pub fn update() -> Result<()> {
-    let repos = crate::repository::new()?;
+#[derive(Debug, Serialize)]
+struct DryRun<'a> {
+    pre_update: Option<&'a str>,
+    post_update: Option<&'a str>,
+    plugins: Vec<UpdatePlan>,
+}
+
+pub fn update(filter: &Filter, opts: &UpdateOptions) -> Result<()> {
+    let repos = crate::repository::new(filter)?;
     let statuses = crate::git::get_status(&repos)?;
+    interrupt::check()?;
+    if opts.dry_run {
+        return dry_run(&statuses, opts);
+    }
     crate::display::display(&statuses);
 
-    if statuses.iter().all(|x| x.status != UpdateStatus::Required) {
+    if !statuses.iter().any(|x| is_updatable(x, opts)) {
         return Ok(());
     }
 
-    git::update(&statuses)?;
-    eprintln!("{}", "Update successful".bold());
-    Ok(())
+    execute(&statuses, opts)
 }
 
-pub fn update_after_checking() -> Result<()> {
-    let repos = crate::repository::new()?;
+pub fn update_after_checking(filter: &Filter, opts: &UpdateOptions) -> Result<()> {
+    let repos = crate::repository::new(filter)?;
     let statuses = crate::git::get_status(&repos)?;
+    interrupt::check()?;
+    if opts.dry_run {
+        return dry_run(&statuses, opts);
+    }
     crate::display::display(&statuses);
 
-    if statuses.iter().all(|x| x.status != UpdateStatus::Required) {
+    if !statuses.iter().any(|x| is_updatable(x, opts)) {
         return Ok(());
     }
 
+    if opts.interactive {
+        return pick_and_execute(&statuses, opts);
+    }
     if is_continued_by_user()? {
-        git::update(&statuses)?;
-        eprintln!("{}", "Update successful".bold());
+        execute(&statuses, opts)?;
     }
     Ok(())
 }
 
+fn pick_and_execute(statuses: &[GitStatus], opts: &UpdateOptions) -> Result<()> {
+    let plans = git::plan_updates(statuses, opts.follow_default_branch);
+    let dirs = match picker::pick(&plans)? {
+        Some(d) if !d.is_empty() => d,
+        _ => {
+            println!("Canceled");
+            return Ok(());
+        }
+    };
+    let selected: Vec<GitStatus> = statuses
+        .iter()
+        .filter(|s| dirs.contains(&s.dir))
+        .cloned()
+        .collect();
+    execute(&selected, opts)
+}
+
+fn dry_run(statuses: &[GitStatus], opts: &UpdateOptions) -> Result<()> {
+    let config = crate::config::get()?;
+    let mut plans = git::plan_updates(statuses, opts.follow_default_branch);
+    for p in plans.iter_mut() {
+        let repo = Repository {
+            uri: p.uri.to_string(),
+            dir: p.dir.to_string(),
+            manager: p.manager,
+            ..Default::default()
+        };
+        p.hooks = config
+            .post_update_hook(&repo)
+            .map(|h| vec![h.to_string()])
+            .unwrap_or_default();
+    }
+    let (pre_update, post_update) = if plans.is_empty() {
+        (None, None)
+    } else {
+        (
+            config.hooks.pre_update.as_deref(),
+            config.hooks.post_update.as_deref(),
+        )
+    };
+
+    if opts.json {
+        let plan = DryRun {
+            pre_update,
+            post_update,
+            plugins: plans,
+        };
+        println!("{}", serde_json::to_string(&plan)?);
+        return Ok(());
+    }
+
+    if plans.is_empty() {
+        eprintln!("{}", "Nothing to update".bold());
+        return Ok(());
+    }
+    if let Some(h) = pre_update {
+        println!("Hook: pre-update ({})", h);
+    }
+    for p in &plans {
+        println!(
+            "{} {} {}..{} ({} commits){}",
+            p.uri,
+            format!("{:?}", p.strategy).yellow(),
+            &p.old_commit[..7],
+            &p.new_commit[..7],
+            p.commits,
+            p.target
+                .as_ref()
+                .map(|t| format!(" → {}", t))
+                .unwrap_or_default()
+        );
+        for s in &p.submodules {
+            println!("    submodule: {}", s);
+        }
+        for h in &p.hooks {
+            println!("    hook: {}", h);
+        }
+    }
+    if let Some(h) = post_update {
+        println!("Hook: post-update ({})", h);
+    }
+    eprintln!("{}", "Dry run, nothing was changed".bold());
+    Ok(())
+}
+
+fn execute(statuses: &[GitStatus], opts: &UpdateOptions) -> Result<()> {
+    let _busy = interrupt::busy();
+    let config = crate::config::get()?;
+    let hook_dir = crate::config::config_dir()?;
+    let hook_dir = if hook_dir.exists() {
+        hook_dir
+    } else {
+        std::env::current_dir()?
+    };
+    if let Some(cmd) = &config.hooks.pre_update {
+        let out = hook::run(cmd, &hook_dir, &[], config.hook_timeout())?;
+        print_hook_output("pre-update", &out);
+        if !out.success {
+            return Err(anyhow!("pre-update hook is failure"));
+        }
+    }
+
+    let results = git::update(statuses, opts.follow_default_branch)?;
+    if interrupt::is_interrupted() {
+        print_interrupted_states(statuses, &results, opts);
+        return interrupt::check();
+    }
+    // A hook that cannot run is reported with its plugin and does not stop the others
+    let mut hook_errors = 0;
+    for r in results.iter().filter(|r| r.success) {
+        let repo = Repository {
+            uri: r.uri.to_string(),
+            dir: r.dir.to_string(),
+            manager: r.manager,
+            ..Default::default()
+        };
+        if let Some(cmd) = config.post_update_hook(&repo) {
+            let manager = r.manager.map(|m| m.name()).unwrap_or_default();
+            let envs = [
+                ("DOTPLUGS_URI", r.uri.as_str()),
+                ("DOTPLUGS_DIR", r.dir.as_str()),
+                ("DOTPLUGS_MANAGER", manager),
+                ("DOTPLUGS_OLD_REV", r.old_rev.as_str()),
+                ("DOTPLUGS_NEW_REV", r.new_rev.as_str()),
+            ];
+            match hook::run(cmd, &r.dir, &envs, config.hook_timeout()) {
+                Ok(out) => print_hook_output(&r.uri, &out),
+                Err(e) => {
+                    println!(
+                        "Hook: {} ({}) {}",
+                        r.uri,
+                        cmd,
+                        format!("error: {}", e).red()
+                    );
+                    hook_errors += 1;
+                }
+            }
+        }
+    }
+
+    if let Some(cmd) = &config.hooks.post_update {
+        let dirs: Vec<&str> = results
+            .iter()
+            .filter(|r| r.success)
+            .map(|r| r.dir.as_str())
+            .collect();
+        let dirs = dirs.join("\n");
+        let envs = [("DOTPLUGS_UPDATED_DIRS", dirs.as_str())];
+        let out = hook::run(cmd, &hook_dir, &envs, config.hook_timeout())?;
+        print_hook_output("post-update", &out);
+    }
+
+    let failures: Vec<_> = results.iter().filter(|r| !r.success).collect();
+    for f in &failures {
+        let state = if f.timed_out { "Timed out" } else { "Failed" };
+        println!("{} {}", f.uri, state.red());
+    }
+    if !failures.is_empty() {
+        return Err(anyhow!("{} plugins could not be updated", failures.len()));
+    }
+    if hook_errors > 0 {
+        return Err(anyhow!(
+            "{} post-update hooks could not be run",
+            hook_errors
+        ));
+    }
+    eprintln!("{}", "Update successful".bold());
+    Ok(())
+}
+
+fn print_interrupted_states(
+    statuses: &[GitStatus],
+    results: &[UpdateResult],
+    opts: &UpdateOptions,
+) {
+    for s in statuses.iter().filter(|s| is_updatable(s, opts)) {
+        let state = match results.iter().find(|r| r.dir == s.dir) {
+            Some(r) if r.success => "Updated (hooks skipped)".green(),
+            Some(_) => "Failed or killed".red(),
+            None => "Not started".yellow(),
+        };
+        println!("{} {}", s.uri, state);
+    }
+}
+
+fn print_hook_output(name: &str, out: &HookOutput) {
+    let state = if out.timed_out {
+        "timed out".red()
+    } else if out.success {
+        "ok".green()
+    } else {
+        "failed".red()
+    };
+    println!("Hook: {} ({}) {}", name, out.command, state);
+    for line in out.output.lines() {
+        println!("    {}", line);
+    }
+}
+
+fn is_updatable(status: &GitStatus, opts: &UpdateOptions) -> bool {
+    status.status.needs_update() || (opts.follow_default_branch && status.status.needs_repair())
+}
+
 fn is_continued_by_user() -> Result<bool> {
     eprint!("{}", "Do you want to continue? [Y/n] ".bold());
     let mut answer = String::new();
@@ -0,0 +1,128 @@
+use crate::git;
+use crate::git::UpdatePlan;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use std::io::Write;
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Toggle(Vec<usize>),
+    All,
+    None,
+    Diff(usize),
+    Done,
+    Quit,
+}
+
+pub fn pick(plans: &[UpdatePlan]) -> Result<Option<Vec<String>>> {
+    let mut selected = vec![true; plans.len()];
+    loop {
+        print_plans(plans, &selected);
+        eprint!(
+            "{}",
+            "Toggle [1-N], a: all, n: none, d N: diff, Enter: update, q: quit > ".bold()
+        );
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer)? == 0 {
+            return Ok(None);
+        }
+        match parse_command(&answer, plans.len()) {
+            Ok(Command::Toggle(numbers)) => {
+                for n in numbers {
+                    selected[n - 1] = !selected[n - 1];
+                }
+            }
+            Ok(Command::All) => selected.iter_mut().for_each(|s| *s = true),
+            Ok(Command::None) => selected.iter_mut().for_each(|s| *s = false),
+            Ok(Command::Diff(n)) => {
+                let p = &plans[n - 1];
+                git::show_log(&p.dir, &p.old_commit, &p.new_commit)?;
+            }
+            Ok(Command::Done) => {
+                return Ok(Some(
+                    plans
+                        .iter()
+                        .zip(selected)
+                        .filter(|(_, s)| *s)
+                        .map(|(p, _)| p.dir.to_string())
+                        .collect(),
+                ))
+            }
+            Ok(Command::Quit) => return Ok(None),
+            Err(e) => eprintln!("{}", e.to_string().red()),
+        }
+    }
+}
+
+fn print_plans(plans: &[UpdatePlan], selected: &[bool]) {
+    for (i, (p, s)) in plans.iter().zip(selected).enumerate() {
+        println!(
+            "{} {:>3} {} {} {}",
+            if *s { "[x]" } else { "[ ]" },
+            i + 1,
+            p.uri,
+            format!("({} commits)", p.commits).yellow(),
+            p.subject.as_deref().unwrap_or_default()
+        );
+    }
+}
+
+fn parse_command(input: &str, len: usize) -> Result<Command> {
+    let input = input.trim();
+    match input {
+        "" => return Ok(Command::Done),
+        "a" => return Ok(Command::All),
+        "n" => return Ok(Command::None),
+        "q" => return Ok(Command::Quit),
+        _ => {}
+    }
+    if let Some(n) = input.strip_prefix('d') {
+        return Ok(Command::Diff(parse_number(n.trim(), len)?));
+    }
+
+    let mut numbers = vec![];
+    for word in input.split(|c: char| c == ',' || c.is_whitespace()) {
+        if word.is_empty() {
+            continue;
+        }
+        match word.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (parse_number(from, len)?, parse_number(to, len)?);
+                numbers.extend(from.min(to)..=from.max(to));
+            }
+            None => numbers.push(parse_number(word, len)?),
+        }
+    }
+    Ok(Command::Toggle(numbers))
+}
+
+fn parse_number(s: &str, len: usize) -> Result<usize> {
+    match s.parse::<usize>() {
+        Ok(n) if n >= 1 && n <= len => Ok(n),
+        _ => Err(anyhow!("invalid number: {}", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_command_ok() -> Result<()> {
+        assert_eq!(parse_command("\n", 3)?, Command::Done);
+        assert_eq!(parse_command("a\n", 3)?, Command::All);
+        assert_eq!(parse_command("n", 3)?, Command::None);
+        assert_eq!(parse_command("q", 3)?, Command::Quit);
+        assert_eq!(parse_command("d 2", 3)?, Command::Diff(2));
+        assert_eq!(parse_command("1 3", 3)?, Command::Toggle(vec![1, 3]));
+        assert_eq!(
+            parse_command("3-1,2", 3)?,
+            Command::Toggle(vec![1, 2, 3, 2])
+        );
+        assert!(parse_command("4", 3).is_err());
+        assert!(parse_command("d", 3).is_err());
+        assert!(parse_command("x", 3).is_err());
+        Ok(())
+    }
+}
@@ -1,22 +1,175 @@
+use crate::hosting::history::{self, History};
+use crate::hosting::releases::{self, Range, Releases};
+use crate::hosting::{Lookup, RepoInfo};
+use crate::repository::{Filter, Repository};
 mod table_tui;
 
 use anyhow::Result;
-use prettytable::{cell, row, Table};
+use chrono::{Duration, Utc};
+use log::warn;
+use prettytable::{Cell, Row, Table};
 
-pub fn view() -> Result<()> {
-    let repos = crate::repository::new()?;
-    let github_info = crate::github::repo_info::create_info(&repos)?;
+pub fn view(filter: &Filter, refresh: bool) -> Result<()> {
+    crate::hosting::github::token::allow_credential_helper();
+    let repos = crate::repository::new(filter)?;
+    let lookup = crate::hosting::create_info(&repos, refresh)?;
+    let has_metadata = !lookup.repositories.is_empty() || !lookup.not_found.is_empty();
+    let history = History::load(&history::path()?).unwrap_or_else(|e| {
+        warn!("ignoring broken history: {}", e);
+        History::default()
+    });
 
     let mut table = Table::new();
-    let header = vec!["uri", "star", "updated"];
-    for g in &github_info {
-        table.add_row(row![
-            g.name_with_owner,
-            g.stargazers.to_string(),
-            g.updated_at.format("%Y-%m-%dT%H:%M:%S").to_string()
+    let mut header = vec!["name", "manager", "commit"];
+    if has_metadata {
+        header.extend(&[
+            "star",
+            "trend",
+            "issues",
+            "release",
+            "pushed",
+            "license",
+            "language",
+            "flags",
+            "description",
         ]);
     }
+    for r in &repos {
+        let mut cells = vec![
+            r.remote_url()
+                .map(|u| u.to_string())
+                .unwrap_or_else(|_| r.uri.to_string()),
+            r.manager.map(|m| m.name()).unwrap_or_default().to_string(),
+            crate::git::get_last_commit_time(&r.dir)
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+        ];
+        if has_metadata {
+            cells.extend(hosting_cells(&lookup, &history, r));
+        }
+        table.add_row(Row::new(cells.iter().map(|c| Cell::new(c)).collect()));
+    }
 
-    table_tui::display(&header, &table)?;
+    let title = if lookup.unavailable.is_empty() {
+        "Plugins".to_string()
+    } else {
+        format!(
+            "Plugins (no token found for {}, set one in config.toml or run gh auth login)",
+            lookup.unavailable.join(", ")
+        )
+    };
+    table_tui::display(&title, &header, &table, |i| detail(&repos[i]))?;
     Ok(())
 }
+
+pub fn output_json(filter: &Filter, refresh: bool) -> Result<String> {
+    crate::hosting::github::token::allow_credential_helper();
+    let repos = crate::repository::new(filter)?;
+    let lookup = crate::hosting::create_info(&repos, refresh)?;
+    let j = serde_json::to_string(&lookup)?;
+    Ok(j)
+}
+
+fn detail(repo: &Repository) -> String {
+    match pending_release_notes(repo) {
+        Ok(releases) if releases.notes.is_empty() => {
+            "No releases newer than the checkout".to_string()
+        }
+        Ok(releases) => {
+            let mut text = releases
+                .notes
+                .iter()
+                .map(|n| {
+                    format!(
+                        "{}{}\n\n{}\n",
+                        n.tag_name,
+                        n.published_at
+                            .map(|d| format!(" ({})", d.format("%Y-%m-%d")))
+                            .unwrap_or_default(),
+                        releases::plain_text(&n.body)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            if releases.truncated {
+                text.push_str(&format!(
+                    "\nOnly the latest {} releases were fetched, older ones are not shown\n",
+                    releases::RELEASE_COUNT
+                ));
+            }
+            text
+        }
+        Err(e) => format!("Could not fetch release notes: {}", e),
+    }
+}
+
+// Releases published after the checked out tag or commit
+fn pending_release_notes(repo: &Repository) -> Result<Releases> {
+    let range = Range {
+        current_tag: crate::git::get_head_tag(&repo.dir)?,
+        since: crate::git::get_last_commit_time(&repo.dir)?,
+        tags: None,
+    };
+    releases::fetch(&repo.remote_url()?, &range)
+}
+
+fn hosting_cells(lookup: &Lookup, history: &History, repo: &Repository) -> Vec<String> {
+    let mut cells = vec!["".to_string(); 9];
+    let remote = match repo.remote_url() {
+        Ok(r) => r,
+        _ => return cells,
+    };
+    let g = match lookup.get(&remote) {
+        Some(g) => g,
+        None => {
+            if lookup.is_not_found(&remote) {
+                cells[7] = "not found".to_string();
+            }
+            return cells;
+        }
+    };
+    vec![
+        g.stargazers.to_string(),
+        trend(history, remote.host.as_deref().unwrap_or_default(), g),
+        g.open_issues.to_string(),
+        release(g),
+        g.pushed_at
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+        g.license.clone().unwrap_or_default(),
+        g.primary_language.clone().unwrap_or_default(),
+        flags(g),
+        g.description.clone().unwrap_or_default(),
+    ]
+}
+
+// Stars over the last 30 days
+fn trend(history: &History, host: &str, g: &RepoInfo) -> String {
+    let readings = history.get(host, &g.name_with_owner);
+    if readings.len() < 2 {
+        return "".to_string();
+    }
+    let now = Utc::now().naive_utc();
+    history::sparkline(&history::trend(readings, now - Duration::days(30), now, 10))
+}
+
+fn release(g: &RepoInfo) -> String {
+    match &g.latest_release {
+        Some(r) => match r.published_at {
+            Some(d) => format!("{} ({})", r.tag_name, d.format("%Y-%m-%d")),
+            None => r.tag_name.to_string(),
+        },
+        None => "".to_string(),
+    }
+}
+
+fn flags(g: &RepoInfo) -> String {
+    let mut flags = vec![];
+    if g.is_archived {
+        flags.push("archived");
+    }
+    if g.is_fork {
+        flags.push("fork");
+    }
+    flags.join(",")
+}
@@ -1,5 +1,6 @@
 mod event;
 
+use std::collections::HashMap;
 use std::io;
 
 use self::event::{Event, Events};
@@ -13,17 +14,26 @@ use termion::{
 };
 use tui::{
     backend::TermionBackend,
-    layout::{Constraint, Layout},
+    layout::{Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Row, Table, TableState},
+    widgets::{Block, Borders, Paragraph, Row, Table, TableState, Text},
     Frame, Terminal,
 };
 
+const MAX_COLUMN_WIDTH: usize = 40;
+
 struct StatefulTable {
     state: TableState,
     items: prettytable::Table,
 }
 
+// Text shown under the table for the selected row, loaded once per row
+struct DetailPane {
+    visible: bool,
+    scroll: u16,
+    texts: HashMap<usize, String>,
+}
+
 impl StatefulTable {
     pub fn next(&mut self) {
         let i = match self.state.selected() {
@@ -54,7 +64,15 @@ impl StatefulTable {
     }
 }
 
-pub fn display(header: &Vec<&str>, table: &prettytable::Table) -> Result<()> {
+pub fn display<F>(
+    title: &str,
+    header: &Vec<&str>,
+    table: &prettytable::Table,
+    mut detail: F,
+) -> Result<()>
+where
+    F: FnMut(usize) -> String,
+{
     // Terminal initialization
     let stdout = io::stdout().into_raw_mode()?;
     let stdout = MouseTerminal::from(stdout);
@@ -69,11 +87,32 @@ pub fn display(header: &Vec<&str>, table: &prettytable::Table) -> Result<()> {
         state: TableState::default(),
         items: table.clone(),
     };
+    let mut pane = DetailPane {
+        visible: false,
+        scroll: 0,
+        texts: HashMap::new(),
+    };
 
     // Input
     loop {
+        if pane.visible {
+            if let Some(i) = table.state.selected() {
+                pane.texts.entry(i).or_insert_with(|| detail(i));
+            }
+        }
         terminal.draw(|mut f| {
-            render_table(&mut f, &header, &mut table);
+            let rects = Layout::default()
+                .constraints(if pane.visible {
+                    [Constraint::Percentage(50), Constraint::Percentage(50)].as_ref()
+                } else {
+                    [Constraint::Percentage(100)].as_ref()
+                })
+                .margin(5)
+                .split(f.size());
+            render_table(&mut f, rects[0], title, &header, &mut table);
+            if pane.visible {
+                render_detail(&mut f, rects[1], &table, &pane);
+            }
         })?;
 
         match events.next()? {
@@ -86,9 +125,21 @@ pub fn display(header: &Vec<&str>, table: &prettytable::Table) -> Result<()> {
                 }
                 Key::Down => {
                     table.next();
+                    pane.scroll = 0;
                 }
                 Key::Up => {
                     table.previous();
+                    pane.scroll = 0;
+                }
+                Key::Char('\n') => {
+                    pane.visible = !pane.visible && table.state.selected().is_some();
+                    pane.scroll = 0;
+                }
+                Key::PageDown => {
+                    pane.scroll = pane.scroll.saturating_add(10);
+                }
+                Key::PageUp => {
+                    pane.scroll = pane.scroll.saturating_sub(10);
                 }
                 _ => {}
             },
@@ -99,16 +150,16 @@ pub fn display(header: &Vec<&str>, table: &prettytable::Table) -> Result<()> {
     Ok(())
 }
 
+type TermionFrame<'a> =
+    Frame<'a, TermionBackend<AlternateScreen<MouseTerminal<RawTerminal<Stdout>>>>>;
+
 fn render_table(
-    f: &mut Frame<TermionBackend<AlternateScreen<MouseTerminal<RawTerminal<Stdout>>>>>,
+    f: &mut TermionFrame,
+    area: Rect,
+    title: &str,
     header: &Vec<&str>,
     table: &mut StatefulTable,
 ) {
-    let rects = Layout::default()
-        .constraints([Constraint::Percentage(100)].as_ref())
-        .margin(5)
-        .split(f.size());
-
     let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
     let normal_style = Style::default().fg(Color::White);
 
@@ -120,14 +171,48 @@ fn render_table(
             .into_iter();
         Row::StyledData(iter, normal_style)
     });
+    let widths = column_widths(header, &table.items);
     let t = Table::new(header.iter(), rows)
-        .block(Block::default().borders(Borders::ALL).title("Table"))
+        .block(Block::default().borders(Borders::ALL).title(title))
         .highlight_style(selected_style)
         .highlight_symbol(">> ")
-        .widths(&[
-            Constraint::Percentage(50),
-            Constraint::Length(30),
-            Constraint::Max(20),
-        ]);
-    f.render_stateful_widget(t, rects[0], &mut table.state);
+        .widths(&widths);
+    f.render_stateful_widget(t, area, &mut table.state);
+}
+
+fn render_detail(f: &mut TermionFrame, area: Rect, table: &StatefulTable, pane: &DetailPane) {
+    let text = table
+        .state
+        .selected()
+        .and_then(|i| pane.texts.get(&i))
+        .map(|s| s.as_str())
+        .unwrap_or_default();
+    let text = [Text::raw(text)];
+    let p = Paragraph::new(text.iter())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Release notes (Enter to close, PgUp/PgDn to scroll)"),
+        )
+        .wrap(true)
+        .scroll(pane.scroll);
+    f.render_widget(p, area);
+}
+
+fn column_widths(header: &Vec<&str>, items: &prettytable::Table) -> Vec<Constraint> {
+    header
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            let width = items
+                .row_iter()
+                .filter_map(|r| r.get_cell(i))
+                .map(|c| c.get_content().chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(h.len())
+                .min(MAX_COLUMN_WIDTH);
+            Constraint::Length(width as u16)
+        })
+        .collect()
 }
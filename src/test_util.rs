@@ -0,0 +1,33 @@
+use anyhow::{anyhow, Result};
+use boolinator::Boolinator;
+use rand::Rng;
+
+// A fresh path under /tmp such as /tmp/dotplugs_plan_3kX9aQz
+pub fn temp_dir(name: &str) -> String {
+    let suffix = rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(7)
+        .collect::<String>();
+    format!("/tmp/{}_{}", name, suffix)
+}
+
+// Runs git with a fixed identity and branch name, allowing local submodule urls
+pub fn git(args: &[&str], dir: &str) -> Result<()> {
+    std::process::Command::new("git")
+        .args([
+            "-c",
+            "user.name=dotplugs",
+            "-c",
+            "user.email=dotplugs@example.com",
+            "-c",
+            "init.defaultBranch=master",
+            "-c",
+            "protocol.file.allow=always",
+        ])
+        .args(args)
+        .current_dir(dir)
+        .output()?
+        .status
+        .success()
+        .as_result((), anyhow!("git command error"))
+}